@@ -0,0 +1,265 @@
+use anyhow::{Context, Result};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::lockfile::BaseConfigLock;
+use crate::{git, release};
+
+/// Location of the cached base config inside the repository's git directory
+const CACHE_PATH: &str = "gitleaks-guard/base-config.toml";
+
+/// Where an organization base config is fetched from
+#[derive(Debug, PartialEq)]
+pub enum Source {
+    /// Plain HTTP(S) download
+    Url(String),
+    /// File in a git repository, at a branch, tag or commit (`HEAD` when none is given)
+    Git {
+        repo: String,
+        path: String,
+        reference: Option<String>,
+    },
+}
+
+/// A fetched base config
+pub struct Fetched {
+    pub content: String,
+    /// Commit the config was read from, for git sources
+    pub commit: Option<String>,
+}
+
+impl Source {
+    /// Parse an `https://` URL or a `git+<repo>//<path>[@<ref>]` source
+    ///
+    /// ```text
+    /// https://security.example.com/gitleaks/base.toml
+    /// git+https://github.com/acme/security.git//gitleaks/base.toml@v3
+    /// git+git@github.com:acme/security.git//gitleaks/base.toml
+    /// ```
+    pub fn parse(source: &str) -> Result<Self> {
+        if let Some(rest) = source.strip_prefix("git+") {
+            // The `//` separating the file path comes after any `scheme://`
+            let search_from = rest.find("://").map(|i| i + 3).unwrap_or(0);
+            let (repo, file) = rest[search_from..]
+                .find("//")
+                .map(|i| rest.split_at(search_from + i))
+                .with_context(|| format!("'{}' has no file path; use git+<repo>//<path>[@<ref>]", source))?;
+            let file = &file[2..];
+            let (path, reference) = match file.rsplit_once('@') {
+                Some((path, reference)) => (path, Some(reference.to_string())),
+                None => (file, None),
+            };
+            if repo.is_empty() || path.is_empty() || reference.as_deref() == Some("") {
+                anyhow::bail!("'{}' is not a valid git source; use git+<repo>//<path>[@<ref>]", source);
+            }
+            return Ok(Source::Git {
+                repo: repo.to_string(),
+                path: path.to_string(),
+                reference,
+            });
+        }
+        if source.starts_with("https://") || source.starts_with("http://") {
+            return Ok(Source::Url(source.to_string()));
+        }
+        anyhow::bail!(
+            "'{}' is not a supported source; use an https:// URL or git+<repo>//<path>[@<ref>]",
+            source
+        )
+    }
+
+    /// Download the config; `commit` overrides the git reference so a pin can be fetched exactly
+    pub fn fetch(&self, commit: Option<&str>) -> Result<Fetched> {
+        match self {
+            Source::Url(url) => {
+                let content = release::blocking(|| release::fetch_text(&release::client()?, url))?;
+                Ok(Fetched { content, commit: None })
+            }
+            Source::Git { repo, path, reference } => {
+                let reference = commit.or(reference.as_deref()).unwrap_or("HEAD");
+                let (content, commit) = git::fetch_file(repo, reference, path)?;
+                Ok(Fetched {
+                    content,
+                    commit: Some(commit),
+                })
+            }
+        }
+    }
+}
+
+/// State of the cached base config compared to its pin
+#[derive(Debug, PartialEq)]
+pub enum CacheState {
+    Missing,
+    Verified,
+    /// The cached file no longer matches the pinned digest
+    Modified { actual: String },
+}
+
+/// Hex-encoded SHA-256 digest of a config
+pub fn digest(content: &str) -> String {
+    format!("{:x}", Sha256::digest(content.as_bytes()))
+}
+
+/// Path of the cached base config inside the git directory
+///
+/// Like `git rev-parse --git-path`, the result is relative to the current directory (absolute
+/// in a linked worktree); [`extend_path`] turns it into the path written to `[extend] path`.
+pub fn cache_path() -> Result<PathBuf> {
+    git::git_path(CACHE_PATH)
+}
+
+/// Path to write into `[extend] path` for the cached base config at `cache`
+///
+/// `.gitleaks.toml` is committed and gitleaks runs from the top of the working tree, so the
+/// path is made relative to `toplevel`; an absolute path would only work on this machine.
+pub fn extend_path(cache: &Path, toplevel: &Path) -> Result<String> {
+    let cache = cache
+        .canonicalize()
+        .with_context(|| format!("Failed to resolve {}", cache.display()))?;
+    let toplevel = toplevel
+        .canonicalize()
+        .with_context(|| format!("Failed to resolve {}", toplevel.display()))?;
+    let relative = relative_to(&cache, &toplevel).with_context(|| {
+        format!(
+            "{} cannot be referenced relative to {}",
+            cache.display(),
+            toplevel.display()
+        )
+    })?;
+    Ok(relative.to_string_lossy().replace('\\', "/"))
+}
+
+/// Express `path` relative to `base`, or `None` when they share no root (e.g. Windows drives)
+fn relative_to(path: &Path, base: &Path) -> Option<PathBuf> {
+    let path: Vec<_> = path.components().collect();
+    let base: Vec<_> = base.components().collect();
+    let common = path.iter().zip(&base).take_while(|(a, b)| a == b).count();
+    if common == 0 {
+        return None;
+    }
+
+    let mut relative = PathBuf::new();
+    for _ in common..base.len() {
+        relative.push("..");
+    }
+    for component in &path[common..] {
+        relative.push(component.as_os_str());
+    }
+    (relative.is_relative() && !relative.as_os_str().is_empty()).then_some(relative)
+}
+
+/// Compare the cached base config at `path` against `lock`
+pub fn check_cache(path: &Path, lock: &BaseConfigLock) -> Result<CacheState> {
+    if !path.exists() {
+        return Ok(CacheState::Missing);
+    }
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    let actual = digest(&content);
+    if actual.eq_ignore_ascii_case(&lock.sha256) {
+        Ok(CacheState::Verified)
+    } else {
+        Ok(CacheState::Modified { actual })
+    }
+}
+
+/// Store a fetched base config at `path`
+pub fn write_cache(path: &Path, content: &str) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create {}", parent.display()))?;
+    }
+    fs::write(path, content).with_context(|| format!("Failed to write {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_sources() {
+        assert_eq!(
+            Source::parse("https://example.com/base.toml").unwrap(),
+            Source::Url("https://example.com/base.toml".to_string())
+        );
+        assert_eq!(
+            Source::parse("git+https://github.com/acme/security.git//gitleaks/base.toml@v3").unwrap(),
+            Source::Git {
+                repo: "https://github.com/acme/security.git".to_string(),
+                path: "gitleaks/base.toml".to_string(),
+                reference: Some("v3".to_string()),
+            }
+        );
+        assert_eq!(
+            Source::parse("git+git@github.com:acme/security.git//base.toml").unwrap(),
+            Source::Git {
+                repo: "git@github.com:acme/security.git".to_string(),
+                path: "base.toml".to_string(),
+                reference: None,
+            }
+        );
+        assert!(Source::parse("git+https://github.com/acme/security.git").is_err());
+        assert!(Source::parse("git+https://github.com/acme/security.git//base.toml@").is_err());
+        assert!(Source::parse("ftp://example.com/base.toml").is_err());
+    }
+
+    #[test]
+    fn detects_modified_cache() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("nested").join("base.toml");
+        let lock = BaseConfigLock {
+            source: "https://example.com/base.toml".to_string(),
+            sha256: digest("title = \"Org\"\n"),
+            commit: None,
+        };
+
+        assert_eq!(check_cache(&path, &lock).unwrap(), CacheState::Missing);
+        write_cache(&path, "title = \"Org\"\n").unwrap();
+        assert_eq!(check_cache(&path, &lock).unwrap(), CacheState::Verified);
+        fs::write(&path, "title = \"Tampered\"\n").unwrap();
+        assert!(matches!(check_cache(&path, &lock).unwrap(), CacheState::Modified { .. }));
+    }
+
+    #[test]
+    fn extend_path_is_relative_in_linked_worktree() {
+        let dir = tempfile::tempdir().unwrap();
+        let main = dir.path().join("main");
+        let worktree = dir.path().join("feature");
+        let git = |cwd: &Path, args: &[&str]| {
+            let output = std::process::Command::new("git")
+                .current_dir(cwd)
+                .args(["-c", "user.name=Test", "-c", "user.email=test@example.com"])
+                .args(args)
+                .output()
+                .unwrap();
+            assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+            String::from_utf8_lossy(&output.stdout).trim().to_string()
+        };
+        fs::create_dir_all(&main).unwrap();
+        git(&main, &["init", "-q"]);
+        git(&main, &["commit", "-q", "--allow-empty", "-m", "init"]);
+        git(&main, &["worktree", "add", "-q", worktree.to_str().unwrap()]);
+
+        // A linked worktree reports its git directory by absolute path
+        let cache = PathBuf::from(git(&worktree, &["rev-parse", "--git-path", CACHE_PATH]));
+        assert!(cache.is_absolute());
+        write_cache(&cache, "title = \"Org\"\n").unwrap();
+
+        let toplevel = PathBuf::from(git(&worktree, &["rev-parse", "--show-toplevel"]));
+        let path = extend_path(&cache, &toplevel).unwrap();
+        assert!(Path::new(&path).is_relative());
+        assert_eq!(
+            toplevel.join(&path).canonicalize().unwrap(),
+            cache.canonicalize().unwrap()
+        );
+
+        // The main checkout gets the usual path inside .git
+        let cache = PathBuf::from(git(&main, &["rev-parse", "--git-path", CACHE_PATH]));
+        write_cache(&main.join(&cache), "title = \"Org\"\n").unwrap();
+        assert_eq!(
+            extend_path(&main.join(&cache), &main).unwrap(),
+            ".git/gitleaks-guard/base-config.toml"
+        );
+    }
+}
@@ -0,0 +1,145 @@
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::{gitleaks, utils};
+
+/// Content-addressed store of verified gitleaks release archives
+///
+/// Archives live at `<root>/<sha256>/<asset name>`; the asset name carries the version and
+/// platform, the directory the digest, so a cached file can always be re-verified.
+pub struct ArchiveCache {
+    root: PathBuf,
+}
+
+/// A cached release archive
+#[derive(Debug)]
+pub struct CacheEntry {
+    pub name: String,
+    pub digest: String,
+    pub size: u64,
+}
+
+impl ArchiveCache {
+    /// Open the cache under the user's cache directory
+    pub fn open() -> Result<Self> {
+        let dir = utils::cache_dir().context("Could not determine the cache directory")?;
+        Ok(Self::at(dir.join("archives")))
+    }
+
+    /// Open a cache rooted at `root`
+    pub fn at(root: PathBuf) -> Self {
+        ArchiveCache { root }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Return the cached archive for `digest`/`name`, evicting it if its contents no longer match
+    pub fn lookup(&self, digest: &str, name: &str) -> Option<PathBuf> {
+        let dir = self.root.join(digest.to_lowercase());
+        let path = dir.join(name);
+        if !path.is_file() {
+            return None;
+        }
+        match gitleaks::sha256_file(&path) {
+            Ok(actual) if actual.eq_ignore_ascii_case(digest) => Some(path),
+            _ => {
+                fs::remove_dir_all(&dir).ok();
+                None
+            }
+        }
+    }
+
+    /// Copy a verified archive into the cache
+    pub fn store(&self, archive: &Path, digest: &str, name: &str) -> Result<PathBuf> {
+        let dir = self.root.join(digest.to_lowercase());
+        fs::create_dir_all(&dir)
+            .with_context(|| format!("Failed to create cache directory {}", dir.display()))?;
+        let path = dir.join(name);
+        fs::copy(archive, &path)
+            .with_context(|| format!("Failed to cache {}", name))?;
+        Ok(path)
+    }
+
+    /// List all cached archives, sorted by name
+    pub fn entries(&self) -> Result<Vec<CacheEntry>> {
+        let mut entries = Vec::new();
+        if !self.root.exists() {
+            return Ok(entries);
+        }
+
+        let digests = fs::read_dir(&self.root)
+            .with_context(|| format!("Failed to read {}", self.root.display()))?;
+        for digest_dir in digests.filter_map(|e| e.ok()).filter(|e| e.path().is_dir()) {
+            let digest = digest_dir.file_name().to_string_lossy().to_string();
+            for file in fs::read_dir(digest_dir.path())?.filter_map(|e| e.ok()) {
+                let metadata = file.metadata()?;
+                if metadata.is_file() {
+                    entries.push(CacheEntry {
+                        name: file.file_name().to_string_lossy().to_string(),
+                        digest: digest.clone(),
+                        size: metadata.len(),
+                    });
+                }
+            }
+        }
+
+        entries.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(entries)
+    }
+
+    /// Remove every cached archive, returning the number of bytes freed
+    pub fn clean(&self) -> Result<u64> {
+        let freed = self.entries()?.iter().map(|e| e.size).sum();
+        if self.root.exists() {
+            fs::remove_dir_all(&self.root)
+                .with_context(|| format!("Failed to remove {}", self.root.display()))?;
+        }
+        Ok(freed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NAME: &str = "gitleaks_8.18.0_linux_x64.tar.gz";
+
+    #[test]
+    fn stores_and_finds_archives_by_digest() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = ArchiveCache::at(dir.path().join("archives"));
+        let archive = dir.path().join(NAME);
+        fs::write(&archive, b"archive").unwrap();
+        let digest = gitleaks::sha256_file(&archive).unwrap();
+
+        assert!(cache.lookup(&digest, NAME).is_none());
+        cache.store(&archive, &digest, NAME).unwrap();
+        assert!(cache.lookup(&digest, NAME).is_some());
+
+        let entries = cache.entries().unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].name, NAME);
+        assert_eq!(entries[0].digest, digest);
+
+        assert_eq!(cache.clean().unwrap(), 7);
+        assert!(cache.entries().unwrap().is_empty());
+    }
+
+    #[test]
+    fn evicts_corrupted_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = ArchiveCache::at(dir.path().join("archives"));
+        let archive = dir.path().join(NAME);
+        fs::write(&archive, b"archive").unwrap();
+        let digest = gitleaks::sha256_file(&archive).unwrap();
+
+        let cached = cache.store(&archive, &digest, NAME).unwrap();
+        fs::write(&cached, b"tampered").unwrap();
+
+        assert!(cache.lookup(&digest, NAME).is_none());
+        assert!(!cached.exists());
+    }
+}
@@ -0,0 +1,97 @@
+use anyhow::{Context, Result};
+use colored::*;
+use std::fs;
+use std::path::Path;
+
+use crate::config::{self, AllowKind};
+use crate::utils;
+
+/// Add an entry to the `[allowlist]` of `config_path`
+pub fn add(config_path: &Path, kind: AllowKind, entry: &str) -> Result<()> {
+    match kind {
+        AllowKind::Path | AllowKind::Regex => {
+            if let Err(e) = regex::Regex::new(entry) {
+                utils::print_error(&format!("'{}' is not a valid regex: {}", entry, e));
+                anyhow::bail!("Invalid regex");
+            }
+        }
+        AllowKind::Commit => {
+            if entry.len() < 7 || entry.len() > 40 || !entry.chars().all(|c| c.is_ascii_hexdigit()) {
+                utils::print_error(&format!("'{}' is not a commit SHA", entry));
+                anyhow::bail!("Invalid commit SHA");
+            }
+        }
+    }
+
+    let content = read_config(config_path)?;
+    match config::add_allow(&content, kind, entry)? {
+        Some(updated) => {
+            write_config(config_path, &updated)?;
+            utils::print_success(&format!(
+                "Allowed {} {} in {}",
+                kind.label(),
+                entry.cyan(),
+                config_path.display()
+            ));
+        }
+        None => utils::print_info(&format!(
+            "{} {} is already allowed in {}",
+            kind.label(),
+            entry,
+            config_path.display()
+        )),
+    }
+    Ok(())
+}
+
+/// Remove an entry from every `[allowlist]` array it appears in
+pub fn remove(config_path: &Path, entry: &str) -> Result<()> {
+    let content = read_config(config_path)?;
+    let (removed, updated) = config::remove_allow(&content, None, entry)?;
+    if removed.is_empty() {
+        utils::print_error(&format!("'{}' is not in the allowlist of {}", entry, config_path.display()));
+        anyhow::bail!("Allowlist entry not found");
+    }
+
+    write_config(config_path, &updated)?;
+    let kinds: Vec<&str> = removed.iter().map(|k| k.label()).collect();
+    utils::print_success(&format!("Removed {} {} from the allowlist", kinds.join("/"), entry.cyan()));
+    Ok(())
+}
+
+/// Print the `[allowlist]` entries grouped by kind
+pub fn list(config_path: &Path) -> Result<()> {
+    let content = read_config(config_path)?;
+    let entries = config::allow_entries(&content)?;
+    if entries.is_empty() {
+        utils::print_info(&format!("The allowlist in {} is empty.", config_path.display()));
+        return Ok(());
+    }
+
+    for kind in AllowKind::ALL {
+        let values: Vec<&String> = entries.iter().filter(|(k, _)| *k == kind).map(|(_, v)| v).collect();
+        if values.is_empty() {
+            continue;
+        }
+        println!("{}", format!("{} ({}):", kind.key(), values.len()).blue().bold());
+        for value in values {
+            println!("  {}", value);
+        }
+    }
+    Ok(())
+}
+
+fn read_config(path: &Path) -> Result<String> {
+    if !path.exists() {
+        utils::print_error(&format!(
+            "{} not found. Run 'gitleaks-guard install' to create it.",
+            path.display()
+        ));
+        anyhow::bail!("Config file not found");
+    }
+    fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))
+}
+
+fn write_config(path: &Path, content: &str) -> Result<()> {
+    fs::write(path, content).with_context(|| format!("Failed to write {}", path.display()))
+}
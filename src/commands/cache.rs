@@ -0,0 +1,51 @@
+use anyhow::Result;
+use colored::*;
+use comfy_table::{presets::UTF8_FULL, Attribute, Cell, Color, ContentArrangement, Table};
+use indicatif::HumanBytes;
+
+use crate::cache::ArchiveCache;
+use crate::utils;
+
+pub fn list() -> Result<()> {
+    let cache = ArchiveCache::open()?;
+    let entries = cache.entries()?;
+
+    println!("{} {}", "Cache directory:".blue().bold(), cache.root().display());
+    println!();
+
+    if entries.is_empty() {
+        utils::print_info("The download cache is empty.");
+        return Ok(());
+    }
+
+    let mut table = Table::new();
+    table
+        .load_preset(UTF8_FULL)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            Cell::new("Archive").add_attribute(Attribute::Bold).fg(Color::Cyan),
+            Cell::new("SHA-256").add_attribute(Attribute::Bold).fg(Color::Cyan),
+            Cell::new("Size").add_attribute(Attribute::Bold).fg(Color::Cyan),
+        ]);
+
+    for entry in &entries {
+        table.add_row(vec![
+            Cell::new(&entry.name),
+            Cell::new(&entry.digest[..entry.digest.len().min(16)]),
+            Cell::new(HumanBytes(entry.size).to_string()),
+        ]);
+    }
+
+    println!("{table}");
+    let total: u64 = entries.iter().map(|e| e.size).sum();
+    println!();
+    println!("{} archive(s), {}", entries.len(), HumanBytes(total));
+    Ok(())
+}
+
+pub fn clean() -> Result<()> {
+    let cache = ArchiveCache::open()?;
+    let freed = cache.clean()?;
+    utils::print_success(&format!("Download cache cleaned ({} freed)", HumanBytes(freed)));
+    Ok(())
+}
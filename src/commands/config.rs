@@ -0,0 +1,271 @@
+use anyhow::{Context, Result};
+use colored::*;
+use std::fs;
+use std::path::Path;
+
+use crate::base_config::{self, CacheState, Source};
+use crate::config::{self, CONFIG_FILE};
+use crate::lint::{self, Severity};
+use crate::lockfile::{BaseConfigLock, Lockfile, LOCKFILE_NAME};
+use crate::{git, templates, utils};
+
+pub fn validate(path: &Path) -> Result<()> {
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+
+    utils::print_info(&format!("Validating {}...", path.display()));
+    let diagnostics = lint::validate(&content);
+
+    let mut errors = 0;
+    let mut warnings = 0;
+    for diagnostic in &diagnostics {
+        let location = match diagnostic.line {
+            Some(line) => format!("{}:{}", path.display(), line),
+            None => path.display().to_string(),
+        };
+        match diagnostic.severity {
+            Severity::Error => {
+                errors += 1;
+                println!("  {} {}: {}", "✗".red().bold(), location.bold(), diagnostic.message.red());
+            }
+            Severity::Warning => {
+                warnings += 1;
+                println!("  {} {}: {}", "⚠".yellow().bold(), location.bold(), diagnostic.message.yellow());
+            }
+        }
+    }
+
+    if !diagnostics.is_empty() {
+        println!();
+    }
+    if errors > 0 {
+        utils::print_error(&format!("{} error(s), {} warning(s)", errors, warnings));
+        anyhow::bail!("{} is invalid", path.display());
+    }
+    if warnings > 0 {
+        utils::print_warning(&format!("{} is valid with {} warning(s)", path.display(), warnings));
+    } else {
+        utils::print_success(&format!("{} is valid", path.display()));
+    }
+    Ok(())
+}
+
+/// List built-in and custom config templates
+pub fn templates() -> Result<()> {
+    let templates = templates::all()?;
+    let width = templates.iter().map(|t| t.name.len()).max().unwrap_or(0);
+
+    println!("{}", "Config templates:".blue().bold());
+    for template in &templates {
+        let custom = if template.is_custom() { " (custom)".yellow().to_string() } else { String::new() };
+        println!(
+            "  {:width$}  {}{}",
+            template.name.cyan(),
+            template.description,
+            custom,
+            width = width
+        );
+    }
+
+    println!();
+    if let Some(dir) = templates::templates_dir() {
+        utils::print_info(&format!("Add your own as <name>.toml in {}", dir.display()));
+    }
+    utils::print_info("Use one with 'gitleaks-guard init --template <name>' or 'install --template <name>'.");
+    Ok(())
+}
+
+/// Extend `.gitleaks.toml` from an organization base config, pinned by its SHA-256 digest
+pub fn link(source: &str) -> Result<()> {
+    if !git::is_git_repo() {
+        utils::print_error("Not in a git repository.");
+        anyhow::bail!("Not in a git repository");
+    }
+    let config_path = Path::new(CONFIG_FILE);
+    if !config_path.exists() {
+        utils::print_error(&format!("{} not found. Run 'gitleaks-guard install' to create it.", CONFIG_FILE));
+        anyhow::bail!("Config file not found");
+    }
+
+    let parsed = Source::parse(source)?;
+    utils::print_info(&format!("Fetching base config from {}...", source));
+    let fetched = parsed.fetch(None)?;
+    check_base_config(&fetched.content, source)?;
+
+    let cache_path = base_config::cache_path()?;
+    base_config::write_cache(&cache_path, &fetched.content)?;
+    let extend_path = base_config::extend_path(&cache_path, &git::toplevel()?)?;
+    let pin = BaseConfigLock {
+        source: source.to_string(),
+        sha256: base_config::digest(&fetched.content),
+        commit: fetched.commit,
+    };
+    let short = short_digest(&pin.sha256).to_string();
+    Lockfile::update(|lock| lock.base_config = Some(pin))?;
+
+    let content = fs::read_to_string(config_path)
+        .with_context(|| format!("Failed to read {}", CONFIG_FILE))?;
+    let (updated, removed_default) = config::set_extend_path(&content, &extend_path)?;
+    fs::write(config_path, updated).with_context(|| format!("Failed to write {}", CONFIG_FILE))?;
+
+    utils::print_success(&format!("Pinned base config {} in {}", short.cyan(), LOCKFILE_NAME));
+    utils::print_success(&format!("{} now extends {}", CONFIG_FILE, extend_path));
+    if removed_default {
+        utils::print_warning("Removed [extend] useDefault; gitleaks rejects it together with path.");
+    }
+    if extend_path.starts_with("../") {
+        utils::print_warning(
+            "The base config lives outside this working tree (a linked worktree?); \
+             run 'config link' from the main checkout before committing.",
+        );
+    }
+    utils::print_info(&format!(
+        "Commit {} and {}. Teammates run 'gitleaks-guard config sync --locked' to fetch the pinned config.",
+        CONFIG_FILE, LOCKFILE_NAME
+    ));
+    Ok(())
+}
+
+/// Verify the cached base config and refresh it from its source
+///
+/// With `locked` the pin is never moved: the cache is restored from the pinned content and a
+/// changed upstream config is an error.
+pub fn sync(locked: bool) -> Result<()> {
+    let Some(pin) = Lockfile::load()?.and_then(|lock| lock.base_config) else {
+        utils::print_error("No base config linked. Run 'gitleaks-guard config link <source>' first.");
+        anyhow::bail!("No base config linked");
+    };
+
+    let cache_path = base_config::cache_path()?;
+    let cached = base_config::check_cache(&cache_path, &pin)?;
+    match &cached {
+        CacheState::Verified => utils::print_success(&format!(
+            "Cached base config matches the pin ({})",
+            short_digest(&pin.sha256)
+        )),
+        CacheState::Missing => utils::print_info("Base config is not cached yet"),
+        CacheState::Modified { actual } => utils::print_warning(&format!(
+            "{} was modified (expected {}, found {}); restoring it",
+            cache_path.display(),
+            short_digest(&pin.sha256),
+            short_digest(actual)
+        )),
+    }
+
+    let source = Source::parse(&pin.source)?;
+    utils::print_info(&format!("Fetching base config from {}...", pin.source));
+    let fetched = source.fetch(if locked { pin.commit.as_deref() } else { None })?;
+    let sha256 = base_config::digest(&fetched.content);
+
+    if sha256.eq_ignore_ascii_case(&pin.sha256) {
+        if cached != CacheState::Verified {
+            base_config::write_cache(&cache_path, &fetched.content)?;
+            utils::print_success(&format!("Restored the pinned base config to {}", cache_path.display()));
+        } else {
+            utils::print_success("Base config is up to date");
+        }
+        return Ok(());
+    }
+
+    if locked {
+        utils::print_error(&format!(
+            "The base config at {} changed (pinned {}, now {}).",
+            pin.source,
+            short_digest(&pin.sha256),
+            short_digest(&sha256)
+        ));
+        utils::print_info("Run 'gitleaks-guard config sync' without --locked to review and accept the new version.");
+        anyhow::bail!("Base config does not match {}", LOCKFILE_NAME);
+    }
+
+    check_base_config(&fetched.content, &pin.source)?;
+    if cached == CacheState::Verified {
+        let previous = fs::read_to_string(&cache_path)
+            .with_context(|| format!("Failed to read {}", cache_path.display()))?;
+        println!();
+        config::print_diff(&previous, &fetched.content);
+    }
+    base_config::write_cache(&cache_path, &fetched.content)?;
+    let old = short_digest(&pin.sha256).to_string();
+    let new = short_digest(&sha256).to_string();
+    Lockfile::update(|lock| {
+        lock.base_config = Some(BaseConfigLock {
+            sha256,
+            commit: fetched.commit,
+            ..pin
+        })
+    })?;
+    utils::print_success(&format!("Updated the base config pin {} → {}", old, new.cyan()));
+    utils::print_info(&format!("Commit {} so the team picks up the new version.", LOCKFILE_NAME));
+    Ok(())
+}
+
+/// Fetch the pinned base config during setup, if the repository links one
+///
+/// A cached copy that matches the pin is used as is, so offline and mirror installs don't need
+/// the source. A failed fetch only warns, since the rest of the setup is still useful offline.
+pub fn restore_pinned_base() -> Result<()> {
+    let Some(pin) = Lockfile::load()?.and_then(|lock| lock.base_config) else {
+        return Ok(());
+    };
+    let cache_path = base_config::cache_path()?;
+    if base_config::check_cache(&cache_path, &pin)? == CacheState::Verified {
+        utils::print_success(&format!(
+            "Cached base config matches the pin ({})",
+            short_digest(&pin.sha256)
+        ));
+        return Ok(());
+    }
+    if let Err(e) = sync(true) {
+        utils::print_warning(&format!(
+            "Could not fetch the base config: {:#}. Run 'gitleaks-guard config sync --locked' before committing.",
+            e
+        ));
+    }
+    Ok(())
+}
+
+/// Fail with a clear message when the linked base config has not been fetched
+///
+/// gitleaks itself only reports that the extended file is missing, which does not tell a fresh
+/// clone what to do about it.
+pub fn require_base_config() -> Result<()> {
+    let Some(pin) = Lockfile::load()?.and_then(|lock| lock.base_config) else {
+        return Ok(());
+    };
+    let cache_path = base_config::cache_path()?;
+    match base_config::check_cache(&cache_path, &pin)? {
+        CacheState::Verified => Ok(()),
+        CacheState::Missing => {
+            utils::print_error(&format!("{} extends a base config that has not been fetched yet.", CONFIG_FILE));
+            utils::print_info("Run 'gitleaks-guard config sync --locked' first.");
+            anyhow::bail!("Base config not fetched");
+        }
+        CacheState::Modified { .. } => {
+            utils::print_error(&format!("{} does not match the pinned base config.", cache_path.display()));
+            utils::print_info("Run 'gitleaks-guard config sync --locked' to restore it.");
+            anyhow::bail!("Base config does not match {}", LOCKFILE_NAME);
+        }
+    }
+}
+
+/// Refuse base configs gitleaks would fail to load
+fn check_base_config(content: &str, source: &str) -> Result<()> {
+    let errors: Vec<_> = lint::validate(content)
+        .into_iter()
+        .filter(|d| d.severity == Severity::Error)
+        .collect();
+    if errors.is_empty() {
+        return Ok(());
+    }
+    for error in &errors {
+        let line = error.line.map(|l| format!("line {}: ", l)).unwrap_or_default();
+        println!("  {} {}{}", "✗".red().bold(), line, error.message.red());
+    }
+    utils::print_error(&format!("{} is not a valid gitleaks config", source));
+    anyhow::bail!("Invalid base config");
+}
+
+fn short_digest(digest: &str) -> &str {
+    &digest[..digest.len().min(12)]
+}
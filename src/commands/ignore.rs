@@ -0,0 +1,164 @@
+use anyhow::{Context, Result};
+use chrono::Local;
+use colored::*;
+use dialoguer::{theme::ColorfulTheme, Input};
+use std::collections::HashSet;
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::config::CONFIG_FILE;
+use crate::ignore::{IgnoreFile, IGNORE_FILE};
+use crate::report::{self, Finding};
+use crate::{git, gitleaks, utils};
+
+/// Append fingerprints, given directly or taken from a report, to `.gitleaksignore`
+pub fn add(
+    fingerprints: Vec<String>,
+    from_report: Option<PathBuf>,
+    rule: Option<String>,
+    reason: Option<String>,
+) -> Result<()> {
+    let mut fingerprints = fingerprints;
+    if let Some(report_path) = &from_report {
+        let findings: Vec<Finding> = report::load(report_path)?
+            .into_iter()
+            .filter(|f| rule.as_deref().is_none_or(|rule| f.rule_id == rule))
+            .collect();
+        if findings.is_empty() {
+            utils::print_warning(&format!(
+                "No matching findings in {}{}",
+                report_path.display(),
+                rule.map(|r| format!(" for rule {}", r)).unwrap_or_default()
+            ));
+            return Ok(());
+        }
+        for finding in &findings {
+            println!("  {} {}:{} ({})", "•".cyan(), finding.file, finding.start_line, finding.rule_id);
+        }
+        fingerprints.extend(findings.into_iter().map(|f| f.fingerprint).filter(|f| !f.is_empty()));
+    }
+
+    if fingerprints.is_empty() {
+        utils::print_error("Nothing to ignore. Pass fingerprints or --from-report <file>.");
+        anyhow::bail!("No fingerprints given");
+    }
+    for fingerprint in &fingerprints {
+        if !fingerprint.contains(':') {
+            utils::print_error(&format!(
+                "'{}' is not a gitleaks fingerprint (expected [commit:]file:rule:line)",
+                fingerprint
+            ));
+            anyhow::bail!("Invalid fingerprint");
+        }
+    }
+
+    let reason = justification(reason)?;
+    let author = git::user_identity().unwrap_or_else(|| "unknown".to_string());
+    let date = Local::now().format("%Y-%m-%d").to_string();
+
+    let path = Path::new(IGNORE_FILE);
+    let mut ignore_file = IgnoreFile::load_from(path)?;
+    let mut added = 0;
+    for fingerprint in &fingerprints {
+        if ignore_file.add(fingerprint, &reason, &author, &date) {
+            added += 1;
+        } else {
+            utils::print_info(&format!("Already ignored: {}", fingerprint));
+        }
+    }
+
+    if added > 0 {
+        ignore_file.save_to(path)?;
+        utils::print_success(&format!("Added {} fingerprint(s) to {}", added, IGNORE_FILE));
+        utils::print_info(&format!("Commit {} so the whole team skips these findings.", IGNORE_FILE));
+    }
+    Ok(())
+}
+
+/// Remove entries from `.gitleaksignore` that no longer match any finding
+pub fn prune(dry_run: bool) -> Result<()> {
+    let path = Path::new(IGNORE_FILE);
+    if !path.exists() {
+        utils::print_info(&format!("No {} in this repository.", IGNORE_FILE));
+        return Ok(());
+    }
+    if !gitleaks::is_installed() {
+        utils::print_error("Gitleaks is not installed. Please run 'gitleaks-guard install' first.");
+        anyhow::bail!("Gitleaks not installed");
+    }
+
+    utils::print_info("Scanning for current findings (ignoring .gitleaksignore)...");
+    let findings = scan_without_ignores()?;
+    let current: HashSet<String> = findings
+        .iter()
+        .flat_map(|f| [f.fingerprint.clone(), f.location_fingerprint()])
+        .collect();
+
+    let mut ignore_file = IgnoreFile::load_from(path)?;
+    let removed = ignore_file.prune(|fingerprint| current.contains(fingerprint));
+    if removed.is_empty() {
+        utils::print_success(&format!("Every entry in {} still matches a finding.", IGNORE_FILE));
+        return Ok(());
+    }
+
+    for fingerprint in &removed {
+        println!("  {} {}", "-".red(), fingerprint);
+    }
+    if dry_run {
+        utils::print_info(&format!("Dry run - {} stale entry(ies) would be removed.", removed.len()));
+        return Ok(());
+    }
+    ignore_file.save_to(path)?;
+    utils::print_success(&format!("Removed {} stale entry(ies) from {}", removed.len(), IGNORE_FILE));
+    Ok(())
+}
+
+/// Ask for a justification unless one was given; required so every ignore is explained
+fn justification(reason: Option<String>) -> Result<String> {
+    if let Some(reason) = reason.filter(|r| !r.trim().is_empty()) {
+        return Ok(reason);
+    }
+    if !std::io::stdin().is_terminal() {
+        utils::print_error("A justification is required. Pass it with --reason \"...\".");
+        anyhow::bail!("Missing justification");
+    }
+    let reason: String = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt("Why is this finding safe to ignore?")
+        .validate_with(|input: &String| {
+            if input.trim().is_empty() {
+                Err("A justification is required")
+            } else {
+                Ok(())
+            }
+        })
+        .interact_text()?;
+    Ok(reason)
+}
+
+/// Scan the repository with the same detect invocation as the hook, but without ignore entries
+fn scan_without_ignores() -> Result<Vec<Finding>> {
+    let work_dir = tempfile::tempdir().context("Failed to create temporary directory")?;
+    let report_path = work_dir.path().join("report.json");
+
+    let mut command = Command::new("gitleaks");
+    command
+        .args(["detect", "--source", ".", "--no-banner", "--redact"])
+        .args(["--log-level", "error"])
+        .args(["--exit-code", "0"])
+        .args(["--report-format", "json"])
+        .arg("--report-path")
+        .arg(&report_path)
+        // Point gitleaks at an empty directory so it doesn't apply .gitleaksignore
+        .arg("--gitleaks-ignore-path")
+        .arg(work_dir.path());
+    if Path::new(CONFIG_FILE).exists() {
+        command.args(["--config", CONFIG_FILE]);
+    }
+
+    let output = command.output().context("Failed to run gitleaks")?;
+    if !output.status.success() {
+        anyhow::bail!("gitleaks scan failed: {}", String::from_utf8_lossy(&output.stderr).trim());
+    }
+    report::load(&report_path)
+}
@@ -1,11 +1,22 @@
-use anyhow::{Context, Result};
+use anyhow::Result;
 use colored::*;
 use dialoguer::{theme::ColorfulTheme, Confirm, Select};
 use console::Term;
+use std::path::PathBuf;
 
+use crate::lockfile::{Lockfile, LOCKFILE_NAME};
+use crate::templates::{self, Template, TemplateSource};
 use crate::{git, gitleaks, utils};
 
-pub async fn run() -> Result<()> {
+pub async fn run(
+    install_dir: Option<PathBuf>,
+    template: Option<String>,
+    fetch_latest_config: bool,
+    force: bool,
+) -> Result<()> {
+    // Resolve --template up front so a typo fails before anything is installed
+    let template = template.map(|name| templates::find(&name)).transpose()?;
+
     let term = Term::stdout();
     term.clear_screen()?;
 
@@ -60,45 +71,44 @@ pub async fn run() -> Result<()> {
         println!("{} {}", "Architecture:".blue().bold(), arch);
         println!();
 
-        gitleaks::install_gitleaks(&os, &arch)?;
-
-        let version = gitleaks::get_version()?;
+        // Honor a committed lockfile so the wizard installs the team's gitleaks version
+        let lock = Lockfile::gitleaks()?;
+        if let Some(lock) = &lock {
+            utils::print_info(&format!(
+                "Using gitleaks {} pinned in {}",
+                lock.version, LOCKFILE_NAME
+            ));
+        }
+        let options = gitleaks::InstallOptions {
+            version: lock.as_ref().map(|l| l.version.clone()),
+            install_dir: match install_dir {
+                Some(dir) => dir,
+                None => gitleaks::default_install_dir()?,
+            },
+            pinned_checksums: lock.map(|l| l.checksums).unwrap_or_default(),
+            ..Default::default()
+        };
+        let installed = gitleaks::install_gitleaks(&os, &arch, &options)?;
+
+        let version = gitleaks::get_version_at(&installed.path)?;
         utils::print_success(&format!("Gitleaks installed successfully! (version: {})", version));
     }
 
-    // Step 2: Configuration options
+    // Step 2: Configuration template
     println!();
-    let config_options = vec![
-        "Standard (Recommended) - Detects common secrets",
-        "Strict - More aggressive detection",
-        "Minimal - Basic detection only",
-        "Custom - I'll configure it myself later",
-    ];
-
-    let config_selection = Select::with_theme(&ColorfulTheme::default())
-        .with_prompt("Choose gitleaks configuration level")
-        .default(0)
-        .items(&config_options)
-        .interact()?;
-
-    match config_selection {
-        0 => {
+    let template = match template {
+        Some(template) => Some(template),
+        None => choose_template()?,
+    };
+    match template {
+        Some(template) if template.source == TemplateSource::Standard => {
             utils::print_info("Creating standard configuration...");
-            gitleaks::create_config()?;
+            gitleaks::create_config(fetch_latest_config, force)?;
         }
-        1 => {
-            utils::print_info("Creating strict configuration...");
-            gitleaks::create_strict_config()?;
-        }
-        2 => {
-            utils::print_info("Creating minimal configuration...");
-            gitleaks::create_minimal_config()?;
-        }
-        3 => {
-            utils::print_info("Skipping configuration - you can create .gitleaks.toml manually");
-        }
-        _ => unreachable!(),
+        Some(template) => gitleaks::create_template_config(&template, force)?,
+        None => utils::print_info("Skipping configuration - you can create .gitleaks.toml manually"),
     }
+    super::config::restore_pinned_base()?;
 
     // Step 3: Setup pre-commit hook
     println!();
@@ -181,3 +191,23 @@ fn print_completion_summary(hook_enabled: bool) {
     println!("  {} - Update gitleaks", "gitleaks-guard update".cyan());
     println!();
 }
+
+/// Offer every template plus a "configure it myself" option; `None` skips the config
+fn choose_template() -> Result<Option<Template>> {
+    let templates = templates::all()?;
+    let mut items: Vec<String> = templates
+        .iter()
+        .map(|t| {
+            let custom = if t.is_custom() { " (custom)" } else { "" };
+            format!("{}{} - {}", t.name, custom, t.description)
+        })
+        .collect();
+    items.push("Custom - I'll configure it myself later".to_string());
+
+    let selection = Select::with_theme(&ColorfulTheme::default())
+        .with_prompt("Choose a gitleaks configuration template")
+        .default(0)
+        .items(&items)
+        .interact()?;
+    Ok(templates.get(selection).cloned())
+}
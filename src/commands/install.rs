@@ -1,15 +1,44 @@
-use anyhow::{Context, Result};
+use anyhow::Result;
 use colored::*;
+use std::path::PathBuf;
 
+use crate::lockfile::{GitleaksLock, Lockfile, LOCKFILE_NAME};
+use crate::templates::{self, TemplateSource};
 use crate::{git, gitleaks, utils};
 
-pub async fn run(skip_download: bool) -> Result<()> {
+/// How `install` creates `.gitleaks.toml`
+#[derive(Debug, Default)]
+pub struct ConfigOptions {
+    /// Template name; the bundled (or mirrored) config when `None`
+    pub template: Option<String>,
+    /// Download the latest upstream config instead of the bundled one
+    pub fetch_latest: bool,
+    /// Overwrite an existing config without asking
+    pub force: bool,
+}
+
+pub async fn run(
+    skip_download: bool,
+    version: Option<String>,
+    install_dir: Option<PathBuf>,
+    write_lock: bool,
+    source: gitleaks::ArchiveSource,
+    config: ConfigOptions,
+) -> Result<()> {
     // Check if we're in a git repository
     if !git::is_git_repo() {
         utils::print_error("Not a git repository. Please run this command in a git repository.");
         anyhow::bail!("Not a git repository");
     }
 
+    // Resolve --template up front so a typo fails before anything is downloaded
+    let template = config
+        .template
+        .as_deref()
+        .map(templates::find)
+        .transpose()?
+        .filter(|t| t.source != TemplateSource::Standard);
+
     // Detect OS and architecture
     let os = utils::detect_os()?;
     let arch = utils::detect_arch()?;
@@ -17,15 +46,82 @@ pub async fn run(skip_download: bool) -> Result<()> {
     println!("{} {}", "Operating System:".blue().bold(), os);
     println!("{} {}", "Architecture:".blue().bold(), arch);
 
+    // A repository lockfile decides which gitleaks version is installed
+    let lock = Lockfile::gitleaks()?;
+    let version = match (&lock, version) {
+        (Some(lock), Some(requested))
+            if gitleaks::normalize_tag(&requested) != gitleaks::normalize_tag(&lock.version) =>
+        {
+            anyhow::bail!(
+                "--version {} conflicts with gitleaks {} pinned in {}. Use 'gitleaks-guard update' to change the lock.",
+                requested,
+                lock.version,
+                LOCKFILE_NAME
+            );
+        }
+        (Some(lock), _) => {
+            utils::print_info(&format!(
+                "Using gitleaks {} pinned in {}",
+                lock.version, LOCKFILE_NAME
+            ));
+            Some(lock.version.clone())
+        }
+        (None, requested) => requested,
+    };
+
+    // An installed gitleaks only counts if it matches the lock
+    let satisfies_lock = match &lock {
+        Some(lock) => gitleaks::get_version()
+            .map(|v| lock.is_satisfied_by(&v))
+            .unwrap_or(false),
+        None => true,
+    };
+
     // Install gitleaks
-    if !skip_download || !gitleaks::is_installed() {
-        gitleaks::install_gitleaks(&os, &arch)?;
+    let installed = if !skip_download || !gitleaks::is_installed() || !satisfies_lock {
+        let options = gitleaks::InstallOptions {
+            version,
+            install_dir: match install_dir {
+                Some(dir) => dir,
+                None => gitleaks::default_install_dir()?,
+            },
+            pinned_checksums: lock
+                .as_ref()
+                .map(|l| l.checksums.clone())
+                .unwrap_or_default(),
+            source: source.clone(),
+        };
+        Some(gitleaks::install_gitleaks(&os, &arch, &options)?)
     } else {
         utils::print_info("Skipping gitleaks download (already installed)");
+        None
+    };
+
+    // Record the installed release, or fill in digests missing from a hand-written lock
+    if let Some(installed) = &installed {
+        match lock {
+            Some(mut lock) if lock.checksums.is_empty() => {
+                lock.checksums = installed.checksums.clone();
+                Lockfile::update(|l| l.gitleaks = Some(lock))?;
+                utils::print_success(&format!("Recorded archive checksums in {}", LOCKFILE_NAME));
+            }
+            None if write_lock => {
+                let pin = GitleaksLock::new(&installed.tag, installed.checksums.clone());
+                Lockfile::update(|l| l.gitleaks = Some(pin))?;
+                utils::print_success(&format!("Pinned gitleaks {} in {}", installed.tag, LOCKFILE_NAME));
+            }
+            _ => {}
+        }
+    } else if write_lock && lock.is_none() {
+        utils::print_warning("--lock needs a fresh download to record checksums; rerun without --skip-download");
     }
 
     // Check version
-    match gitleaks::get_version() {
+    let version = match &installed {
+        Some(installed) => gitleaks::get_version_at(&installed.path),
+        None => gitleaks::get_version(),
+    };
+    match version {
         Ok(version) => {
             println!("{} {}", "Gitleaks version:".green().bold(), version);
         }
@@ -36,7 +132,15 @@ pub async fn run(skip_download: bool) -> Result<()> {
     }
 
     // Create configuration
-    gitleaks::create_config()?;
+    match (&template, &source) {
+        (Some(template), _) => gitleaks::create_template_config(template, config.force)?,
+        (None, gitleaks::ArchiveSource::GitHub) => gitleaks::create_config(config.fetch_latest, config.force)?,
+        (None, gitleaks::ArchiveSource::Mirror(dir)) => gitleaks::create_offline_config(Some(dir), config.force)?,
+        (None, gitleaks::ArchiveSource::Archive(_)) => gitleaks::create_offline_config(None, config.force)?,
+    }
+
+    // Fetch the organization base config the repository extends
+    super::config::restore_pinned_base()?;
 
     // Create pre-commit hook
     gitleaks::create_pre_commit_hook()?;
@@ -1,7 +1,15 @@
+pub mod allow;
+pub mod cache;
+pub mod config;
+pub mod ignore;
 pub mod init;
 pub mod install;
 pub mod toggle;
+pub mod rules;
 pub mod scan;
+pub mod self_update;
 pub mod version;
 pub mod status;
 pub mod update;
+pub mod uninstall;
+pub mod use_version;
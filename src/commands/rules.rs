@@ -0,0 +1,393 @@
+use anyhow::{Context, Result};
+use colored::*;
+use comfy_table::{presets::UTF8_FULL, Attribute, Cell, Color, ContentArrangement, Table};
+use dialoguer::{theme::ColorfulTheme, Confirm, Input};
+use regex::Regex;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::io::IsTerminal;
+use std::path::Path;
+
+use crate::config::{self, NewRule};
+use crate::rule_tests::{self, ConfigRules, Fixtures, SampleOutcome};
+use crate::{gitleaks, lint, utils};
+
+/// Append a custom rule to `config_path` after checking it against sample input
+///
+/// Runs interactively when `--id` or `--regex` is missing and a terminal is attached, prompting
+/// for every field and at least one sample; otherwise the flags are used as given.
+pub fn add(config_path: &Path, rule: NewRule, samples: Vec<String>) -> Result<()> {
+    if !config_path.exists() {
+        utils::print_error(&format!(
+            "{} not found. Run 'gitleaks-guard install' to create it.",
+            config_path.display()
+        ));
+        anyhow::bail!("Config file not found");
+    }
+
+    let interactive = rule.id.is_empty() || rule.regex.is_empty();
+    if interactive && !std::io::stdin().is_terminal() {
+        utils::print_error("--id and --regex are required when not running interactively.");
+        anyhow::bail!("Missing rule id or regex");
+    }
+    let (rule, samples) = if interactive {
+        prompt_rule(rule, samples)?
+    } else {
+        (rule, samples)
+    };
+
+    if let Err(e) = check_id(&rule.id) {
+        utils::print_error(&e);
+        anyhow::bail!("Invalid rule id");
+    }
+    let regex = match compile(&rule.regex, rule.secret_group) {
+        Ok(regex) => regex,
+        Err(e) => {
+            utils::print_error(&e);
+            anyhow::bail!("Invalid rule regex");
+        }
+    };
+    if rule.entropy.is_some_and(|e| !(0.0..=8.0).contains(&e)) {
+        utils::print_error("--entropy must be between 0 and 8 bits per character.");
+        anyhow::bail!("Invalid entropy");
+    }
+
+    let content = fs::read_to_string(config_path)
+        .with_context(|| format!("Failed to read {}", config_path.display()))?;
+    let updated = match config::add_rule(&content, &rule) {
+        Ok(updated) => updated,
+        Err(e) => {
+            utils::print_error(&format!("{:#}", e));
+            anyhow::bail!("Rule not added");
+        }
+    };
+
+    if samples.is_empty() {
+        utils::print_warning("No --sample given; the rule was not tried on any input.");
+    } else if !report_samples(&rule, &regex, &samples) {
+        let add_anyway = interactive
+            && Confirm::with_theme(&ColorfulTheme::default())
+                .with_prompt("Not every sample produces a finding. Add the rule anyway?")
+                .default(false)
+                .interact()?;
+        if !add_anyway {
+            utils::print_error("Rule not added.");
+            anyhow::bail!("Rule does not match its samples");
+        }
+    }
+
+    fs::write(config_path, updated)
+        .with_context(|| format!("Failed to write {}", config_path.display()))?;
+
+    println!("{}", rule.to_toml().dimmed());
+    utils::print_success(&format!("Added rule {} to {}", rule.id.cyan(), config_path.display()));
+    utils::print_info(&format!(
+        "Add samples under [rules.{}] in {} and run 'gitleaks-guard rules test' to check it with gitleaks.",
+        rule.id,
+        rule_tests::FIXTURES_FILE
+    ));
+    Ok(())
+}
+
+/// Ask for every rule field, using values given as flags as defaults
+fn prompt_rule(rule: NewRule, samples: Vec<String>) -> Result<(NewRule, Vec<String>)> {
+    let theme = ColorfulTheme::default();
+
+    let id: String = Input::with_theme(&theme)
+        .with_prompt("Rule id (e.g. acme-api-token)")
+        .with_initial_text(rule.id)
+        .validate_with(|input: &String| check_id(input))
+        .interact_text()?;
+    let description: String = Input::with_theme(&theme)
+        .with_prompt("Description")
+        .with_initial_text(rule.description)
+        .allow_empty(true)
+        .interact_text()?;
+    let regex: String = Input::with_theme(&theme)
+        .with_prompt("Regex")
+        .with_initial_text(rule.regex)
+        .validate_with(|input: &String| compile(input, None).map(|_| ()))
+        .interact_text()?;
+    let keywords: String = Input::with_theme(&theme)
+        .with_prompt("Keywords, comma separated (speeds up scans; a sample must contain one)")
+        .with_initial_text(rule.keywords.join(", "))
+        .allow_empty(true)
+        .interact_text()?;
+
+    let groups = Regex::new(&regex).map(|r| r.captures_len() - 1).unwrap_or(0);
+    let secret_group = if groups > 0 {
+        let group: String = Input::with_theme(&theme)
+            .with_prompt(format!("Capture group holding the secret (1-{}, empty for the first)", groups))
+            .with_initial_text(rule.secret_group.map(|g| g.to_string()).unwrap_or_default())
+            .allow_empty(true)
+            .validate_with(|input: &String| match input.trim() {
+                "" => Ok(()),
+                value => match value.parse::<usize>() {
+                    Ok(group) if (1..=groups).contains(&group) => Ok(()),
+                    _ => Err(format!("Enter a number from 1 to {}", groups)),
+                },
+            })
+            .interact_text()?;
+        group.trim().parse().ok()
+    } else {
+        None
+    };
+
+    let entropy: String = Input::with_theme(&theme)
+        .with_prompt("Minimum entropy of the secret, e.g. 3.5 (empty for none)")
+        .with_initial_text(rule.entropy.map(|e| e.to_string()).unwrap_or_default())
+        .allow_empty(true)
+        .validate_with(|input: &String| match input.trim() {
+            "" => Ok(()),
+            value => match value.parse::<f64>() {
+                Ok(entropy) if (0.0..=8.0).contains(&entropy) => Ok(()),
+                _ => Err("Enter a number from 0 to 8"),
+            },
+        })
+        .interact_text()?;
+    let tags: String = Input::with_theme(&theme)
+        .with_prompt("Tags, comma separated")
+        .with_initial_text(rule.tags.join(", "))
+        .allow_empty(true)
+        .interact_text()?;
+
+    let mut samples = samples;
+    loop {
+        let prompt = if samples.is_empty() {
+            "Sample text the rule must catch"
+        } else {
+            "Another sample (empty to finish)"
+        };
+        let sample: String = Input::with_theme(&theme)
+            .with_prompt(prompt)
+            .allow_empty(!samples.is_empty())
+            .interact_text()?;
+        if sample.is_empty() {
+            break;
+        }
+        samples.push(sample);
+    }
+
+    let rule = NewRule {
+        id: id.trim().to_string(),
+        description: description.trim().to_string(),
+        regex,
+        keywords: split_list(&keywords),
+        entropy: entropy.trim().parse().ok(),
+        secret_group,
+        tags: split_list(&tags),
+    };
+    Ok((rule, samples))
+}
+
+/// Print what the rule finds in each sample; returns whether every sample produces a finding
+fn report_samples(rule: &NewRule, regex: &Regex, samples: &[String]) -> bool {
+    println!();
+    let mut all_found = true;
+    for sample in samples {
+        let detail = match rule_tests::try_rule(rule, regex, sample) {
+            SampleOutcome::Finding { secret, entropy } => {
+                println!("  {} {}", "✓".green().bold(), format!("{:?}", sample).dimmed());
+                format!("secret {:?}, entropy {:.2}", secret, entropy)
+            }
+            outcome => {
+                all_found = false;
+                println!("  {} {}", "✗".red().bold(), format!("{:?}", sample).dimmed());
+                match outcome {
+                    SampleOutcome::NoMatch => "regex does not match".to_string(),
+                    SampleOutcome::MissingKeyword => {
+                        format!("contains none of the keywords: {}", rule.keywords.join(", "))
+                    }
+                    SampleOutcome::LowEntropy { secret, entropy } => format!(
+                        "secret {:?} has entropy {:.2}, not above {}",
+                        secret,
+                        entropy,
+                        rule.entropy.unwrap_or_default()
+                    ),
+                    SampleOutcome::Finding { .. } => unreachable!(),
+                }
+            }
+        };
+        println!("      {}", detail);
+    }
+    println!();
+    all_found
+}
+
+fn check_id(id: &str) -> std::result::Result<(), String> {
+    let id = id.trim();
+    if id.is_empty() {
+        Err("The rule id can't be empty".to_string())
+    } else if !id.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.')) {
+        Err(format!("'{}' is not a valid rule id (use letters, digits, '-', '_' and '.')", id))
+    } else {
+        Ok(())
+    }
+}
+
+/// Compile a rule regex, checking `secret_group` refers to one of its capture groups
+///
+/// Rust's `regex` syntax closely follows Go's RE2, which gitleaks uses.
+fn compile(pattern: &str, secret_group: Option<u32>) -> std::result::Result<Regex, String> {
+    let regex = Regex::new(pattern).map_err(|e| format!("'{}' is not a valid regex: {}", pattern, lint::regex_error(&e)))?;
+    let groups = regex.captures_len() - 1;
+    match secret_group {
+        Some(group) if group as usize > groups => Err(format!(
+            "secretGroup {} does not exist; the regex has {} capture group(s)",
+            group, groups
+        )),
+        _ => Ok(regex),
+    }
+}
+
+fn split_list(input: &str) -> Vec<String> {
+    input
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+pub fn test(fixtures_path: &Path, config: &Path) -> Result<()> {
+    if !gitleaks::is_installed() {
+        utils::print_error("Gitleaks is not installed. Please run 'gitleaks-guard install' first.");
+        anyhow::bail!("Gitleaks not installed");
+    }
+    if !config.exists() {
+        utils::print_error(&format!("{} not found.", config.display()));
+        anyhow::bail!("Config file not found");
+    }
+
+    let fixtures = Fixtures::load(fixtures_path)?;
+    let loaded = ConfigRules::load(config)?;
+    let samples = fixtures.samples();
+    if samples.is_empty() {
+        utils::print_warning(&format!("{} contains no samples.", fixtures_path.display()));
+        return Ok(());
+    }
+
+    utils::print_info(&format!(
+        "Testing {} sample(s) from {} against {}...",
+        samples.len(),
+        fixtures_path.display(),
+        config.display()
+    ));
+    let found = rule_tests::scan_samples(&samples, config)?;
+
+    // A section for a rule the config doesn't load would let its negative samples pass vacuously
+    let reported: BTreeSet<&str> = found.iter().flatten().map(String::as_str).collect();
+    let mut unknown = BTreeSet::new();
+    for rule in fixtures.rules.keys() {
+        if loaded.ids.contains(rule) || reported.contains(rule.as_str()) {
+            continue;
+        }
+        if loaded.extends_remote {
+            utils::print_warning(&format!(
+                "Rule `{}` is not defined in {}; assuming it is one of gitleaks' default rules.",
+                rule,
+                config.display()
+            ));
+        } else {
+            unknown.insert(rule.as_str());
+        }
+    }
+
+    // (positive passed, positive total, negative passed, negative total) per rule
+    let mut matrix: BTreeMap<&str, [usize; 4]> = BTreeMap::new();
+    let mut failures = Vec::new();
+    for (sample, found) in samples.iter().zip(&found) {
+        let counts = matrix.entry(sample.rule.as_str()).or_default();
+        let passed = unknown.contains(sample.rule.as_str()) || sample.passed(found);
+        let offset = if sample.expect_match { 0 } else { 2 };
+        counts[offset + 1] += 1;
+        if passed {
+            counts[offset] += 1;
+        } else {
+            failures.push((sample, found));
+        }
+    }
+
+    let mut table = Table::new();
+    table
+        .load_preset(UTF8_FULL)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            Cell::new("Rule").add_attribute(Attribute::Bold).fg(Color::Cyan),
+            Cell::new("Positive").add_attribute(Attribute::Bold).fg(Color::Cyan),
+            Cell::new("Negative").add_attribute(Attribute::Bold).fg(Color::Cyan),
+            Cell::new("Result").add_attribute(Attribute::Bold).fg(Color::Cyan),
+        ]);
+    for (rule, [pos_ok, pos_total, neg_ok, neg_total]) in &matrix {
+        let ok = pos_ok == pos_total && neg_ok == neg_total;
+        let known = !unknown.contains(rule);
+        table.add_row(vec![
+            Cell::new(rule),
+            Cell::new(if known { format!("{}/{}", pos_ok, pos_total) } else { "-".to_string() }),
+            Cell::new(if known { format!("{}/{}", neg_ok, neg_total) } else { "-".to_string() }),
+            if !known {
+                Cell::new("UNKNOWN").fg(Color::Red)
+            } else if ok {
+                Cell::new("PASS").fg(Color::Green)
+            } else {
+                Cell::new("FAIL").fg(Color::Red)
+            },
+        ]);
+    }
+    println!();
+    println!("{table}");
+
+    if failures.is_empty() && unknown.is_empty() {
+        println!();
+        utils::print_success(&format!("All {} sample(s) passed", samples.len()));
+        return Ok(());
+    }
+
+    println!();
+    for (sample, found) in &failures {
+        let detail = if sample.expect_match {
+            "expected a finding, got none".to_string()
+        } else {
+            "expected no finding, but the rule matched".to_string()
+        };
+        let others: Vec<&str> = found
+            .iter()
+            .map(String::as_str)
+            .filter(|rule| *rule != sample.rule)
+            .collect();
+        let others = if others.is_empty() {
+            String::new()
+        } else {
+            format!(" (other rules matched: {})", others.join(", "))
+        };
+        println!(
+            "  {} {}: {}{}\n      {}",
+            "✗".red().bold(),
+            sample.rule.bold(),
+            detail,
+            others.dimmed(),
+            format!("{:?}", sample.text).dimmed()
+        );
+    }
+    for rule in &unknown {
+        println!(
+            "  {} unknown rule `{}`: not defined in {}",
+            "✗".red().bold(),
+            rule.bold(),
+            config.display()
+        );
+    }
+    println!();
+    if !failures.is_empty() {
+        utils::print_error(&format!("{} of {} sample(s) failed", failures.len(), samples.len()));
+    }
+    if !unknown.is_empty() {
+        utils::print_error(&format!(
+            "{} rule id(s) in {} are not loaded by {}",
+            unknown.len(),
+            fixtures_path.display(),
+            config.display()
+        ));
+    }
+    anyhow::bail!("Rule tests failed");
+}
@@ -27,7 +27,7 @@ pub async fn run(url: Option<String>, path: Option<String>, cleanup: bool) -> Re
         let clone_dir = PathBuf::from("cloned_repos").join(
             repo_url
                 .split('/')
-                .last()
+                .next_back()
                 .unwrap_or("repo")
                 .trim_end_matches(".git"),
         );
@@ -68,6 +68,9 @@ pub async fn run(url: Option<String>, path: Option<String>, cleanup: bool) -> Re
         }
     } else {
         // Scan current directory
+        if git::is_git_repo() {
+            super::config::require_base_config()?;
+        }
         scan_path = env::current_dir().context("Failed to get current directory")?;
         should_cleanup = false;
         utils::print_info("Scanning current directory...");
@@ -0,0 +1,179 @@
+use anyhow::{Context, Result};
+use colored::*;
+use dialoguer::{theme::ColorfulTheme, Confirm};
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::{gitleaks, release, utils};
+
+#[cfg(windows)]
+const EXE_NAME: &str = "gitleaks-guard.exe";
+#[cfg(not(windows))]
+const EXE_NAME: &str = "gitleaks-guard";
+
+pub async fn run(force: bool, yes: bool) -> Result<()> {
+    let current = env!("CARGO_PKG_VERSION");
+    println!("{} {}", "Current version:".blue().bold(), current);
+
+    let api_url = release::self_api_url();
+    let asset_name = asset_name()?;
+
+    release::blocking(|| {
+        let client = release::client()?;
+        utils::print_info("Checking for gitleaks-guard updates...");
+        let latest = release::fetch_release(&client, &api_url, None)?;
+        let latest_version = latest.tag_name.trim_start_matches('v').to_string();
+        println!("{} {}", "Latest version:".blue().bold(), latest_version);
+        println!();
+
+        let newer = match (semver::Version::parse(current), semver::Version::parse(&latest_version)) {
+            (Ok(current), Ok(latest)) => latest > current,
+            _ => current != latest_version,
+        };
+        if !newer && !force {
+            utils::print_success(&format!("gitleaks-guard {} is up to date!", current));
+            return Ok(());
+        }
+
+        let proceed = yes
+            || Confirm::with_theme(&ColorfulTheme::default())
+                .with_prompt(format!("Update gitleaks-guard {} → {}?", current, latest_version))
+                .default(true)
+                .interact()?;
+        if !proceed {
+            utils::print_info("Self-update cancelled.");
+            return Ok(());
+        }
+
+        let asset = latest.asset(&asset_name).with_context(|| {
+            format!("Release {} has no build for this platform (looking for: {})", latest.tag_name, asset_name)
+        })?;
+        let checksums_asset = latest.checksums_asset().with_context(|| {
+            format!(
+                "Release {} does not publish a checksums file; refusing to install unverified binary",
+                latest.tag_name
+            )
+        })?;
+        let checksums = release::fetch_text(&client, &checksums_asset.browser_download_url)
+            .context("Failed to download release checksums")?;
+        let expected = gitleaks::parse_checksum_entries(&checksums)
+            .remove(&asset.name)
+            .with_context(|| format!("{} is not listed in {}", asset.name, checksums_asset.name))?;
+
+        let temp_dir = tempfile::tempdir()?;
+        let download_path = temp_dir.path().join(&asset.name);
+        utils::print_info(&format!("Downloading gitleaks-guard {}...", latest.tag_name));
+        let pb = release::progress_bar();
+        release::download_file(&client, &asset.browser_download_url, &download_path, &pb)
+            .context("Failed to download gitleaks-guard")?;
+        pb.finish_with_message("Download complete");
+
+        utils::print_info("Verifying checksum...");
+        gitleaks::verify_checksum(&download_path, &expected)?;
+        utils::print_success("Checksum verified");
+
+        let new_binary = unpack(&download_path, temp_dir.path())?;
+        smoke_test(&new_binary, &latest_version)?;
+
+        let exe = env::current_exe().context("Failed to locate the running gitleaks-guard")?;
+        replace_executable(&new_binary, &exe)?;
+
+        println!();
+        utils::print_success(&format!(
+            "gitleaks-guard updated: {} → {} ({})",
+            current,
+            latest_version.green().bold(),
+            exe.display()
+        ));
+        Ok(())
+    })
+}
+
+/// Release asset name built by the release workflow for this platform
+fn asset_name() -> Result<String> {
+    let os = match env::consts::OS {
+        "linux" => "linux",
+        "macos" => "macos",
+        "windows" => "windows",
+        other => anyhow::bail!("No gitleaks-guard builds are published for {}", other),
+    };
+    let arch = match env::consts::ARCH {
+        "x86_64" => "x64",
+        "aarch64" => "arm64",
+        other => anyhow::bail!("No gitleaks-guard builds are published for {} {}", os, other),
+    };
+    let suffix = if os == "windows" { ".exe" } else { "" };
+    Ok(format!("gitleaks-guard-{}-{}{}", os, arch, suffix))
+}
+
+/// Return the executable inside a downloaded asset, extracting it first if it is an archive
+fn unpack(download: &Path, work_dir: &Path) -> Result<PathBuf> {
+    let name = download.to_string_lossy();
+    if !name.ends_with(".tar.gz") && !name.ends_with(".zip") {
+        return Ok(download.to_path_buf());
+    }
+
+    let extract_dir = work_dir.join("extract");
+    gitleaks::extract_archive(download, &extract_dir)?;
+    let binary = extract_dir.join(EXE_NAME);
+    if !binary.is_file() {
+        anyhow::bail!("{} not found in downloaded archive", EXE_NAME);
+    }
+    Ok(binary)
+}
+
+/// Run the new binary once so a broken download never replaces a working executable
+fn smoke_test(binary: &Path, expected_version: &str) -> Result<()> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(binary, fs::Permissions::from_mode(0o755))
+            .context("Failed to make gitleaks-guard executable")?;
+    }
+
+    let output = Command::new(binary)
+        .arg("--version")
+        .output()
+        .context("The downloaded gitleaks-guard does not run on this host")?;
+    let reported = String::from_utf8_lossy(&output.stdout);
+    if !output.status.success() || !reported.contains(expected_version) {
+        anyhow::bail!(
+            "The downloaded gitleaks-guard reported '{}', expected version {}",
+            reported.trim(),
+            expected_version
+        );
+    }
+    Ok(())
+}
+
+/// Swap `new_binary` in for the running executable with a rename, so it is never half-written
+fn replace_executable(new_binary: &Path, exe: &Path) -> Result<()> {
+    let dir = exe.parent().context("The running executable has no parent directory")?;
+    let staging = dir.join(format!(".{}.new", EXE_NAME));
+    let permission_hint = || {
+        format!(
+            "Failed to write to {}. Rerun with permissions to modify it (e.g. sudo gitleaks-guard self-update)",
+            dir.display()
+        )
+    };
+
+    fs::copy(new_binary, &staging).with_context(permission_hint)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&staging, fs::Permissions::from_mode(0o755))
+            .context("Failed to make gitleaks-guard executable")?;
+    }
+
+    // Windows cannot overwrite a running executable, but it can rename it out of the way
+    #[cfg(windows)]
+    {
+        let old = exe.with_extension("exe.old");
+        fs::remove_file(&old).ok();
+        fs::rename(exe, &old).with_context(permission_hint)?;
+    }
+
+    fs::rename(&staging, exe).with_context(permission_hint)
+}
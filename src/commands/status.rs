@@ -3,7 +3,9 @@ use colored::*;
 use comfy_table::{Table, Cell, Attribute, Color, ContentArrangement, presets::UTF8_FULL};
 use std::path::Path;
 
-use crate::{git, gitleaks, utils};
+use crate::base_config::{self, CacheState};
+use crate::lockfile::{Lockfile, LOCKFILE_NAME};
+use crate::{git, gitleaks};
 
 pub fn run() -> Result<()> {
     print_banner();
@@ -58,6 +60,41 @@ pub fn run() -> Result<()> {
         Cell::new(&gitleaks_version),
     ]);
 
+    // Check the installed version against the repository lockfile
+    let lockfile = Lockfile::load();
+    let lock_satisfied = match lockfile.as_ref().map(|l| l.as_ref().and_then(|l| l.gitleaks.as_ref())) {
+        Err(e) => {
+            table.add_row(vec![
+                Cell::new("Version Lock"),
+                Cell::new("⚠").fg(Color::Yellow),
+                // Keep the table compact: toml errors continue with a source snippet
+                Cell::new(format!("{:#}", e).lines().next().unwrap_or_default()),
+            ]);
+            false
+        }
+        Ok(Some(lock)) => {
+            let satisfied = gitleaks_installed && lock.is_satisfied_by(&gitleaks_version);
+            table.add_row(vec![
+                Cell::new("Version Lock"),
+                if satisfied {
+                    Cell::new("✓").fg(Color::Green)
+                } else {
+                    Cell::new("⚠").fg(Color::Yellow)
+                },
+                if satisfied {
+                    Cell::new(format!("Pinned to {}", lock.version))
+                } else {
+                    Cell::new(format!(
+                        "{} requires {}, installed: {}",
+                        LOCKFILE_NAME, lock.version, gitleaks_version
+                    ))
+                },
+            ]);
+            satisfied
+        }
+        Ok(None) => true,
+    };
+
     // Check configuration file
     let config_exists = Path::new(".gitleaks.toml").exists();
     table.add_row(vec![
@@ -74,6 +111,28 @@ pub fn run() -> Result<()> {
         },
     ]);
 
+    // Check the organization base config against its pin
+    let base_config_ok = match lockfile.ok().flatten().and_then(|lock| lock.base_config) {
+        Some(pin) => {
+            let state = base_config::cache_path().and_then(|path| base_config::check_cache(&path, &pin));
+            let (mark, detail) = match &state {
+                Ok(CacheState::Verified) => (
+                    Cell::new("✓").fg(Color::Green),
+                    format!("Pinned {} from {}", &pin.sha256[..pin.sha256.len().min(12)], pin.source),
+                ),
+                Ok(CacheState::Missing) => (Cell::new("✗").fg(Color::Yellow), "Not fetched yet".to_string()),
+                Ok(CacheState::Modified { .. }) => (
+                    Cell::new("⚠").fg(Color::Red),
+                    "Cached copy was modified since it was pinned".to_string(),
+                ),
+                Err(e) => (Cell::new("⚠").fg(Color::Yellow), e.to_string()),
+            };
+            table.add_row(vec![Cell::new("Base Config"), mark, Cell::new(detail)]);
+            matches!(state, Ok(CacheState::Verified))
+        }
+        None => true,
+    };
+
     // Check pre-commit hook
     let hook_path = Path::new(".git/hooks/pre-commit");
     let hook_exists = hook_path.exists();
@@ -117,7 +176,7 @@ pub fn run() -> Result<()> {
 
     // Overall status summary
     println!();
-    if gitleaks_installed && config_exists && hook_enabled && in_git_repo {
+    if gitleaks_installed && lock_satisfied && config_exists && base_config_ok && hook_enabled && in_git_repo {
         println!("{}", "╔═══════════════════════════════════════════╗".green());
         println!("{}", "║  ✅ Your repository is fully protected!  ║".green().bold());
         println!("{}", "╚═══════════════════════════════════════════╝".green());
@@ -134,9 +193,15 @@ pub fn run() -> Result<()> {
         if !gitleaks_installed {
             println!("  {} Install gitleaks: {}", "•".yellow(), "gitleaks-guard init".cyan());
         }
+        if gitleaks_installed && !lock_satisfied {
+            println!("  {} Install the locked gitleaks version: {}", "•".yellow(), "gitleaks-guard install".cyan());
+        }
         if !config_exists {
             println!("  {} Create configuration: {}", "•".yellow(), "gitleaks-guard init".cyan());
         }
+        if !base_config_ok {
+            println!("  {} Restore the pinned base config: {}", "•".yellow(), "gitleaks-guard config sync --locked".cyan());
+        }
         if !hook_enabled && hook_exists {
             println!("  {} Enable pre-commit hook: {}", "•".yellow(), "gitleaks-guard enable".cyan());
         } else if !hook_exists {
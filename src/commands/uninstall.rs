@@ -0,0 +1,213 @@
+use anyhow::{Context, Result};
+use colored::*;
+use dialoguer::{theme::ColorfulTheme, Confirm};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::base_config;
+use crate::lockfile::LOCKFILE_NAME;
+use crate::versions::VersionStore;
+use crate::{git, gitleaks, utils};
+
+/// What `uninstall` should remove
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scope {
+    /// Repository setup only: config, lockfile, hook and git config key
+    Repo,
+    /// The gitleaks binary only
+    Binary,
+    /// Everything
+    All,
+}
+
+/// A single reversible piece of what `install`/`init` set up
+enum Step {
+    RemoveFile { path: PathBuf, what: &'static str },
+    RemoveDir { path: PathBuf, what: &'static str },
+    RestoreHook { backup: PathBuf, hook: PathBuf },
+    UnsetGitConfig,
+}
+
+impl Step {
+    fn describe(&self) -> String {
+        match self {
+            Step::RemoveFile { path, what } | Step::RemoveDir { path, what } => {
+                format!("Remove {} ({})", what, path.display())
+            }
+            Step::RestoreHook { backup, hook } => format!(
+                "Restore previous pre-commit hook ({} → {})",
+                backup.display(),
+                hook.display()
+            ),
+            Step::UnsetGitConfig => "Unset git config hooks.gitleaks-enable".to_string(),
+        }
+    }
+
+    fn apply(&self) -> Result<()> {
+        match self {
+            Step::RemoveFile { path, what } => fs::remove_file(path).with_context(|| {
+                format!("Failed to remove {} ({}). You may need elevated permissions.", what, path.display())
+            }),
+            Step::RemoveDir { path, what } => fs::remove_dir_all(path).with_context(|| {
+                format!("Failed to remove {} ({}). You may need elevated permissions.", what, path.display())
+            }),
+            Step::RestoreHook { backup, hook } => {
+                fs::rename(backup, hook).context("Failed to restore previous pre-commit hook")
+            }
+            Step::UnsetGitConfig => git::unset_gitleaks_config(),
+        }
+    }
+}
+
+pub fn run(scope: Scope, dry_run: bool, yes: bool) -> Result<()> {
+    print_banner();
+
+    let mut steps = Vec::new();
+    if scope != Scope::Binary {
+        if !git::is_git_repo() {
+            utils::print_error("Not a git repository. Please run this command in a git repository.");
+            anyhow::bail!("Not a git repository");
+        }
+        steps.extend(repo_steps());
+    }
+    if scope != Scope::Repo {
+        steps.extend(binary_steps()?);
+    }
+
+    if steps.is_empty() {
+        utils::print_success("Nothing to uninstall.");
+        return Ok(());
+    }
+
+    println!("{}", if dry_run { "Would perform:" } else { "Will perform:" }.bold());
+    for step in &steps {
+        println!("  {} {}", "•".cyan(), step.describe());
+    }
+    println!();
+
+    if dry_run {
+        utils::print_info("Dry run - nothing was changed.");
+        return Ok(());
+    }
+
+    let proceed = yes
+        || Confirm::with_theme(&ColorfulTheme::default())
+            .with_prompt("Proceed with uninstall?")
+            .default(false)
+            .interact()?;
+    if !proceed {
+        utils::print_info("Uninstall cancelled.");
+        return Ok(());
+    }
+
+    for step in &steps {
+        step.apply()?;
+        utils::print_success(&step.describe());
+    }
+
+    println!();
+    println!("{}", "═══════════════════════════════".green());
+    println!("{} {}", "✔".green().bold(), "Uninstall completed".green().bold());
+    println!("{}", "═══════════════════════════════".green());
+    Ok(())
+}
+
+/// Collect the repository-level steps that reverse `install`/`init`
+fn repo_steps() -> Vec<Step> {
+    let mut steps = Vec::new();
+
+    for (file, what) in [
+        (".gitleaks.toml", "gitleaks configuration"),
+        (LOCKFILE_NAME, "lockfile"),
+        ("gitleaks-report.json", "last hook report"),
+    ] {
+        if Path::new(file).exists() {
+            steps.push(Step::RemoveFile {
+                path: PathBuf::from(file),
+                what,
+            });
+        }
+    }
+
+    if let Ok(path) = base_config::cache_path() {
+        if path.exists() {
+            steps.push(Step::RemoveFile {
+                path,
+                what: "cached base config",
+            });
+        }
+    }
+
+    let hooks_dir = Path::new(".git/hooks");
+    let hook = hooks_dir.join("pre-commit");
+    let backup = hooks_dir.join(gitleaks::HOOK_BACKUP_NAME);
+    if gitleaks::is_our_hook(&hook) {
+        if backup.exists() {
+            steps.push(Step::RestoreHook { backup, hook });
+        } else {
+            steps.push(Step::RemoveFile {
+                path: hook,
+                what: "pre-commit hook",
+            });
+        }
+    } else if hook.exists() {
+        utils::print_warning("The pre-commit hook was not installed by gitleaks-guard; leaving it in place");
+    }
+
+    if git::has_gitleaks_config() {
+        steps.push(Step::UnsetGitConfig);
+    }
+
+    steps
+}
+
+/// Collect the steps that remove the gitleaks binary and the installed versions
+///
+/// Only a binary a version store put in place is removed; a gitleaks installed by a package
+/// manager or an administrator is left alone, even in the default install directory.
+fn binary_steps() -> Result<Vec<Step>> {
+    let mut steps = Vec::new();
+    let default_dir = gitleaks::default_install_dir()?;
+    let mut stores = vec![VersionStore::open()?, VersionStore::for_install_dir(&default_dir)?];
+
+    match utils::find_command("gitleaks") {
+        Some(path) => {
+            let dir = path.parent().map(Path::to_path_buf).unwrap_or_default();
+            stores.push(VersionStore::for_install_dir(&dir)?);
+            if stores.iter().any(|store| store.owns(&path)) {
+                steps.push(Step::RemoveFile {
+                    path,
+                    what: "gitleaks binary",
+                });
+            } else {
+                utils::print_warning(&format!(
+                    "{} was not installed by gitleaks-guard; leaving it in place",
+                    path.display()
+                ));
+            }
+        }
+        None => utils::print_info("Gitleaks binary not found in PATH - nothing to remove"),
+    }
+
+    let mut roots: Vec<PathBuf> = Vec::new();
+    for store in &stores {
+        let root = store.root().to_path_buf();
+        if root.exists() && !roots.contains(&root) {
+            roots.push(root);
+        }
+    }
+    steps.extend(roots.into_iter().map(|path| Step::RemoveDir {
+        path,
+        what: "installed gitleaks versions",
+    }));
+
+    Ok(steps)
+}
+
+fn print_banner() {
+    println!();
+    println!("{}", "╔═══════════════════════════════════════════╗".red());
+    println!("{}", "║       🧹 GitLeaks Guard Uninstall 🧹      ║".red().bold());
+    println!("{}", "╚═══════════════════════════════════════════╝".red());
+    println!();
+}
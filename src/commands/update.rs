@@ -2,10 +2,18 @@ use anyhow::Result;
 use colored::*;
 use dialoguer::{theme::ColorfulTheme, Confirm};
 use indicatif::{ProgressBar, ProgressStyle};
+use std::io::IsTerminal;
+use std::path::PathBuf;
 
-use crate::{gitleaks, utils};
+use crate::lockfile::{GitleaksLock, Lockfile, LOCKFILE_NAME};
+use crate::{gitleaks, release, release_notes, utils};
 
-pub async fn run(force: bool) -> Result<()> {
+pub async fn run(
+    force: bool,
+    version: Option<String>,
+    install_dir: Option<PathBuf>,
+    allow_downgrade: bool,
+) -> Result<()> {
     print_banner();
 
     // Check if gitleaks is installed
@@ -19,43 +27,74 @@ pub async fn run(force: bool) -> Result<()> {
     let current_version = gitleaks::get_version()?;
     println!("{} {}", "Current version:".blue().bold(), current_version);
 
-    // Get latest version from GitHub
-    let spinner = ProgressBar::new_spinner();
-    spinner.set_style(
-        ProgressStyle::default_spinner()
-            .template("{spinner:.blue} {msg}")
-            .unwrap(),
-    );
-    spinner.set_message("Checking for updates...");
-    spinner.enable_steady_tick(std::time::Duration::from_millis(80));
+    // Refuse before anything changes if the new binary would not be the one hooks run
+    let install_dir = match install_dir {
+        Some(dir) => dir,
+        None => gitleaks::active_install_dir()?,
+    };
+    gitleaks::ensure_not_shadowed(&install_dir)?;
 
-    let latest_version = match get_latest_version().await {
-        Ok(version) => {
-            spinner.finish_with_message("✓ Update check complete");
-            version
+    // A pinned version skips the latest-release lookup entirely
+    let target_version = match &version {
+        Some(v) => {
+            let tag = gitleaks::normalize_tag(v);
+            println!("{} {}", "Requested version:".blue().bold(), tag);
+            tag.trim_start_matches('v').to_string()
         }
-        Err(e) => {
-            spinner.finish_with_message("✗ Failed to check for updates");
-            utils::print_error(&format!("Could not check for updates: {}", e));
-            anyhow::bail!("Update check failed");
+        None => {
+            let latest_version = check_latest_version().await?;
+            println!("{} {}", "Latest version:".blue().bold(), latest_version);
+            latest_version
         }
     };
-
-    println!("{} {}", "Latest version:".blue().bold(), latest_version);
     println!();
 
     // Compare versions
-    let needs_update = compare_versions(&current_version, &latest_version);
-
-    if !needs_update && !force {
-        utils::print_success("You are already running the latest version!");
-        return Ok(());
+    let change = compare_versions(&current_version, &target_version);
+    match change {
+        VersionChange::Same if !force => {
+            utils::print_success(&format!("You are already running version {}!", target_version));
+            return Ok(());
+        }
+        VersionChange::Same => {
+            utils::print_warning(&format!(
+                "You are already on version {}, but forcing reinstall...",
+                target_version
+            ));
+        }
+        VersionChange::Downgrade if !allow_downgrade => {
+            utils::print_error(&format!(
+                "Installed gitleaks {} is newer than {}.",
+                current_version, target_version
+            ));
+            utils::print_info("Rerun with --allow-downgrade to install the older version anyway.");
+            anyhow::bail!("Refusing to downgrade gitleaks");
+        }
+        VersionChange::Downgrade => {
+            utils::print_warning(&format!(
+                "Downgrading gitleaks: {} → {}",
+                current_version, target_version
+            ));
+        }
+        VersionChange::Upgrade if version.is_some() => {
+            utils::print_info(&format!(
+                "Switching to requested version: {} → {}",
+                current_version, target_version
+            ));
+        }
+        VersionChange::Upgrade => {
+            utils::print_info(&format!("A new version is available: {} → {}", current_version, target_version));
+        }
+        VersionChange::Unknown => {
+            utils::print_warning(&format!(
+                "Could not compare versions '{}' and '{}'; assuming an update is needed",
+                current_version, target_version
+            ));
+        }
     }
 
-    if !needs_update && force {
-        utils::print_warning("You are already on the latest version, but forcing reinstall...");
-    } else {
-        utils::print_info(&format!("A new version is available: {} → {}", current_version, latest_version));
+    if change == VersionChange::Upgrade {
+        show_release_notes(&current_version, &target_version);
     }
 
     println!();
@@ -80,9 +119,19 @@ pub async fn run(force: bool) -> Result<()> {
     let os = utils::detect_os()?;
     let arch = utils::detect_arch()?;
 
-    gitleaks::install_gitleaks(&os, &arch)?;
+    let lock = Lockfile::gitleaks()?;
+    let options = gitleaks::InstallOptions {
+        version,
+        install_dir,
+        pinned_checksums: lock
+            .as_ref()
+            .map(|l| l.checksums.clone())
+            .unwrap_or_default(),
+        ..Default::default()
+    };
+    let installed = gitleaks::install_gitleaks(&os, &arch, &options)?;
 
-    // Verify installation
+    // Verify installation against the binary git hooks will actually run
     let new_version = gitleaks::get_version()?;
 
     println!();
@@ -93,35 +142,174 @@ pub async fn run(force: bool) -> Result<()> {
     println!("{} {} → {}", "Version:".blue().bold(), current_version, new_version.green().bold());
     println!();
 
+    // Offer to move the repository lock along with the binary
+    if let Some(lock) = lock {
+        if !lock.is_satisfied_by(&installed.tag) {
+            // --force answers yes; without a terminal to ask on, leave the lock alone
+            let bump = if force {
+                true
+            } else if !std::io::stdin().is_terminal() {
+                false
+            } else {
+                Confirm::with_theme(&ColorfulTheme::default())
+                    .with_prompt(format!(
+                        "{} pins gitleaks {}. Update it to {}?",
+                        LOCKFILE_NAME,
+                        lock.version,
+                        installed.tag.trim_start_matches('v')
+                    ))
+                    .default(true)
+                    .interact()?
+            };
+
+            if bump {
+                let pin = GitleaksLock::new(&installed.tag, installed.checksums);
+                Lockfile::update(|l| l.gitleaks = Some(pin))?;
+                utils::print_success(&format!("Updated {} - remember to commit it", LOCKFILE_NAME));
+            } else {
+                utils::print_warning(&format!(
+                    "{} still pins gitleaks {}; 'gitleaks-guard install' will reinstall it",
+                    LOCKFILE_NAME, lock.version
+                ));
+            }
+            println!();
+        }
+    }
+
     Ok(())
 }
 
-async fn get_latest_version() -> Result<String> {
-    let client = reqwest::Client::builder()
-        .user_agent("gitleaks-guard")
-        .build()?;
+/// Look up the latest release with a spinner, reporting failures to the user
+async fn check_latest_version() -> Result<String> {
+    let spinner = ProgressBar::new_spinner();
+    spinner.set_style(
+        ProgressStyle::default_spinner()
+            .template("{spinner:.blue} {msg}")
+            .unwrap(),
+    );
+    spinner.set_message("Checking for updates...");
+    spinner.enable_steady_tick(std::time::Duration::from_millis(80));
 
-    let response = client
-        .get("https://api.github.com/repos/gitleaks/gitleaks/releases/latest")
-        .send()
-        .await?;
+    match get_latest_version().await {
+        Ok(version) => {
+            spinner.finish_with_message("✓ Update check complete");
+            Ok(version)
+        }
+        Err(e) => {
+            spinner.finish_with_message("✗ Failed to check for updates");
+            utils::print_error(&format!("Could not check for updates: {}", e));
+            anyhow::bail!("Update check failed");
+        }
+    }
+}
 
-    let json: serde_json::Value = response.json().await?;
+async fn get_latest_version() -> Result<String> {
+    let api_url = release::gitleaks_api_url()?;
+    let release = release::blocking(|| release::fetch_release(&release::client()?, &api_url, None))?;
+    Ok(release.tag_name.trim_start_matches('v').to_string())
+}
 
-    let tag_name = json["tag_name"]
-        .as_str()
-        .ok_or_else(|| anyhow::anyhow!("Could not parse version"))?
-        .trim_start_matches('v');
+/// How the target version relates to the installed one
+#[derive(Debug, PartialEq, Eq)]
+enum VersionChange {
+    Upgrade,
+    Downgrade,
+    Same,
+    /// One side is not a semantic version (e.g. a custom build)
+    Unknown,
+}
 
-    Ok(tag_name.to_string())
+fn compare_versions(current: &str, target: &str) -> VersionChange {
+    match (gitleaks::parse_version(current), gitleaks::parse_version(target)) {
+        (Some(current), Some(target)) => match target.cmp(&current) {
+            std::cmp::Ordering::Greater => VersionChange::Upgrade,
+            std::cmp::Ordering::Less => VersionChange::Downgrade,
+            std::cmp::Ordering::Equal => VersionChange::Same,
+        },
+        _ => VersionChange::Unknown,
+    }
 }
 
-fn compare_versions(current: &str, latest: &str) -> bool {
-    let current = current.trim_start_matches('v');
-    let latest = latest.trim_start_matches('v');
+/// Entries shown per release before the rest is collapsed; notable entries are always shown
+const NOTES_PER_RELEASE: usize = 8;
 
-    // Simple version comparison (assuming semantic versioning)
-    current != latest
+/// Print condensed notes for every release between the installed and target versions
+///
+/// Release notes are informational, so failing to fetch them only prints a warning.
+fn show_release_notes(current: &str, target: &str) {
+    let (Some(current), Some(target)) = (gitleaks::parse_version(current), gitleaks::parse_version(target)) else {
+        return;
+    };
+    let releases = match release::gitleaks_api_url()
+        .and_then(|api_url| release::blocking(|| release::fetch_releases(&release::client()?, &api_url)))
+    {
+        Ok(releases) => releases,
+        Err(e) => {
+            utils::print_warning(&format!("Could not fetch release notes: {}", e));
+            return;
+        }
+    };
+
+    let between = release_notes::releases_between(&releases, &current, &target);
+    if between.is_empty() {
+        return;
+    }
+
+    println!();
+    println!("{} {} → {}", "Release notes:".blue().bold(), current, target);
+    let mut shown_notable = false;
+    for (version, release) in between {
+        println!();
+        println!("{}", format!("v{}", version).cyan().bold());
+
+        let notes = release_notes::condense(release.body.as_deref().unwrap_or_default());
+        let mut shown_plain = 0;
+        let mut hidden = 0;
+        for note in &notes {
+            if note.notable {
+                shown_notable = true;
+                println!("  {} {}", "!".yellow().bold(), note.text.yellow());
+            } else if shown_plain < NOTES_PER_RELEASE {
+                shown_plain += 1;
+                println!("  • {}", note.text);
+            } else {
+                hidden += 1;
+            }
+        }
+
+        if notes.is_empty() {
+            println!("  {}", "(no release notes)".dimmed());
+        }
+        if hidden > 0 {
+            let more = format!("… {} more", hidden);
+            match &release.html_url {
+                Some(url) => println!("  {} {}", more.dimmed(), url.dimmed()),
+                None => println!("  {}", more.dimmed()),
+            }
+        }
+    }
+    // The list is capped, so it may stop short of the installed release
+    let reaches_current = releases.iter().any(|release| {
+        gitleaks::parse_version(&release.tag_name).is_some_and(|version| version <= current)
+    });
+    if !reaches_current {
+        println!();
+        println!(
+            "  {}",
+            format!(
+                "Only the {} most recent releases were fetched; notes for older ones are not shown.",
+                releases.len()
+            )
+            .dimmed()
+        );
+    }
+    if shown_notable {
+        println!();
+        println!(
+            "{} entries mention rules, config syntax or breaking changes",
+            "!".yellow().bold()
+        );
+    }
 }
 
 fn print_banner() {
@@ -131,3 +319,17 @@ fn print_banner() {
     println!("{}", "╚═══════════════════════════════════════════╝".blue());
     println!();
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classifies_version_changes() {
+        assert_eq!(compare_versions("8.17.0", "8.18.0"), VersionChange::Upgrade);
+        assert_eq!(compare_versions("v8.19.0", "8.18.0"), VersionChange::Downgrade);
+        assert_eq!(compare_versions("v8.18.0", "8.18.0"), VersionChange::Same);
+        assert_eq!(compare_versions("8.18.0", "8.18.0-rc.1"), VersionChange::Downgrade);
+        assert_eq!(compare_versions("custom", "8.18.0"), VersionChange::Unknown);
+    }
+}
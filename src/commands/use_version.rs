@@ -0,0 +1,98 @@
+use anyhow::Result;
+use colored::*;
+use std::path::{Path, PathBuf};
+
+use crate::lockfile::{Lockfile, LOCKFILE_NAME};
+use crate::versions::VersionStore;
+use crate::{gitleaks, utils};
+
+/// Point the install directory at an already installed gitleaks version
+pub fn run(version: &str, install_dir: Option<PathBuf>) -> Result<()> {
+    let install_dir = match install_dir {
+        Some(dir) => dir,
+        None => gitleaks::active_install_dir()?,
+    };
+    gitleaks::ensure_not_shadowed(&install_dir)?;
+    let store = VersionStore::for_install_dir(&install_dir)?;
+    let version = version.trim_start_matches('v');
+
+    if store.binary(version).is_none() {
+        let installed = store.versions()?;
+        if installed.is_empty() {
+            utils::print_error("No gitleaks versions are installed yet.");
+        } else {
+            utils::print_error(&format!(
+                "gitleaks {} is not installed. Installed versions: {}",
+                version,
+                installed.join(", ")
+            ));
+        }
+        utils::print_info(&format!("Install it with 'gitleaks-guard update --version {}'", version));
+        anyhow::bail!("gitleaks {} not installed", version);
+    }
+
+    let path = store.activate(version, &install_dir)?;
+    report_switch(version, &path)
+}
+
+/// Switch back to the version that was active before the last update or `use`
+pub fn rollback(install_dir: Option<PathBuf>) -> Result<()> {
+    let install_dir = match install_dir {
+        Some(dir) => dir,
+        None => gitleaks::active_install_dir()?,
+    };
+    gitleaks::ensure_not_shadowed(&install_dir)?;
+    let store = VersionStore::for_install_dir(&install_dir)?;
+
+    let current = store.current();
+    let (version, path) = store.rollback(&install_dir)?;
+    if let Some(current) = current {
+        utils::print_info(&format!("Rolling back gitleaks {} → {}", current, version));
+    }
+    report_switch(&version, &path)
+}
+
+/// List installed versions, marking the active one
+pub fn list(install_dir: Option<PathBuf>) -> Result<()> {
+    let install_dir = match install_dir.or_else(gitleaks::path_install_dir) {
+        Some(dir) => dir,
+        None => gitleaks::default_install_dir()?,
+    };
+    let store = VersionStore::for_install_dir(&install_dir)?;
+    let versions = store.versions()?;
+
+    if versions.is_empty() {
+        utils::print_info("No gitleaks versions are installed yet.");
+        return Ok(());
+    }
+
+    let current = store.current();
+    println!("{}", "Installed gitleaks versions:".blue().bold());
+    for version in versions {
+        if current.as_deref() == Some(version.as_str()) {
+            println!("  {} {}", "*".green().bold(), version.green().bold());
+        } else {
+            println!("    {}", version);
+        }
+    }
+    Ok(())
+}
+
+fn report_switch(version: &str, path: &Path) -> Result<()> {
+    if !gitleaks::warn_if_shadowed(path) {
+        anyhow::bail!("gitleaks {} was linked to {}, but is not the one on PATH", version, path.display());
+    }
+    let reported = gitleaks::get_version()?;
+    utils::print_success(&format!("Now using gitleaks {} ({})", reported, path.display()));
+
+    // The repository lock still wins on the next install
+    if let Some(lock) = Lockfile::gitleaks()? {
+        if !lock.is_satisfied_by(version) {
+            utils::print_warning(&format!(
+                "{} pins gitleaks {}; 'gitleaks-guard install' will switch back to it",
+                LOCKFILE_NAME, lock.version
+            ));
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,543 @@
+use anyhow::{Context, Result};
+use chrono::Local;
+use colored::*;
+use dialoguer::{theme::ColorfulTheme, Select};
+use similar::{ChangeTag, TextDiff};
+use std::fs;
+use std::io::IsTerminal;
+use std::path::Path;
+use toml_edit::{DocumentMut, Item, Table, Value};
+
+use crate::utils;
+
+/// Repository gitleaks configuration written by `init` and `install`
+pub const CONFIG_FILE: &str = ".gitleaks.toml";
+
+/// Which `[allowlist]` array an entry belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AllowKind {
+    /// File path regex (`paths`)
+    Path,
+    /// Secret regex (`regexes`)
+    Regex,
+    /// Commit SHA (`commits`)
+    Commit,
+}
+
+impl AllowKind {
+    pub const ALL: [AllowKind; 3] = [AllowKind::Path, AllowKind::Regex, AllowKind::Commit];
+
+    /// Key of the array inside `[allowlist]`
+    pub fn key(self) -> &'static str {
+        match self {
+            AllowKind::Path => "paths",
+            AllowKind::Regex => "regexes",
+            AllowKind::Commit => "commits",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            AllowKind::Path => "path",
+            AllowKind::Regex => "regex",
+            AllowKind::Commit => "commit",
+        }
+    }
+}
+
+/// Diff lines shown before the rest is collapsed
+const MAX_DIFF_LINES: usize = 60;
+
+/// Write `.gitleaks.toml`, asking before an existing file is changed
+///
+/// An existing config is only replaced with `force`; otherwise the user sees a diff and picks
+/// keep, overwrite, merge or backup. Without a terminal to ask on, the existing file is kept.
+/// Returns whether the file was written.
+pub fn write(content: &str, force: bool) -> Result<bool> {
+    let path = Path::new(CONFIG_FILE);
+    if !path.exists() || force {
+        fs::write(path, content).context("Failed to write config file")?;
+        return Ok(true);
+    }
+
+    let existing = fs::read_to_string(path).context("Failed to read existing config file")?;
+    if existing == content {
+        utils::print_info(&format!("{} is already up to date", CONFIG_FILE));
+        return Ok(false);
+    }
+
+    if !std::io::stdin().is_terminal() {
+        utils::print_warning(&format!(
+            "{} already exists; keeping it. Use --force to overwrite it.",
+            CONFIG_FILE
+        ));
+        return Ok(false);
+    }
+
+    utils::print_warning(&format!(
+        "{} already exists. The new configuration would change it like this:",
+        CONFIG_FILE
+    ));
+    print_diff(&existing, content);
+
+    let choices = [
+        "Keep existing file",
+        "Overwrite with the new configuration",
+        "Merge - keep my settings, add new rules and allowlist entries",
+        "Back up existing file, then overwrite",
+    ];
+    let choice = Select::with_theme(&ColorfulTheme::default())
+        .with_prompt(format!("What should happen to {}?", CONFIG_FILE))
+        .default(0)
+        .items(&choices)
+        .interact()?;
+
+    match choice {
+        0 => {
+            utils::print_info(&format!("Keeping existing {}", CONFIG_FILE));
+            return Ok(false);
+        }
+        1 => {}
+        2 => {
+            let merged = merge(&existing, content)?;
+            fs::write(path, merged).context("Failed to write config file")?;
+            utils::print_success(&format!("Merged new rules and allowlist entries into {}", CONFIG_FILE));
+            return Ok(true);
+        }
+        3 => {
+            let backup = format!("{}.{}.bak", CONFIG_FILE, Local::now().format("%Y%m%d%H%M%S"));
+            fs::copy(path, &backup).with_context(|| format!("Failed to back up {}", CONFIG_FILE))?;
+            utils::print_success(&format!("Backed up existing configuration to {}", backup));
+        }
+        _ => unreachable!(),
+    }
+
+    fs::write(path, content).context("Failed to write config file")?;
+    Ok(true)
+}
+
+/// Print a coloured line diff between the existing and the new configuration
+pub fn print_diff(old: &str, new: &str) {
+    let diff = TextDiff::from_lines(old, new);
+    let mut shown = 0;
+    let mut hidden = 0;
+
+    for (index, group) in diff.grouped_ops(2).into_iter().enumerate() {
+        if index > 0 && shown < MAX_DIFF_LINES {
+            shown += 1;
+            println!("  {}", "  ...".dimmed());
+        }
+        for op in group {
+            for change in diff.iter_changes(&op) {
+                if shown >= MAX_DIFF_LINES {
+                    hidden += 1;
+                    continue;
+                }
+                shown += 1;
+                let line = change.value().trim_end_matches('\n');
+                match change.tag() {
+                    ChangeTag::Delete => println!("  {}", format!("- {}", line).red()),
+                    ChangeTag::Insert => println!("  {}", format!("+ {}", line).green()),
+                    ChangeTag::Equal => println!("  {}", format!("  {}", line).dimmed()),
+                }
+            }
+        }
+    }
+
+    if hidden > 0 {
+        println!("  {}", format!("… {} more lines", hidden).dimmed());
+    }
+    println!();
+}
+
+/// Merge a new configuration into an existing one without overriding anything already set
+///
+/// Missing keys and tables are added, string arrays such as allowlist `paths` gain the new
+/// entries, and `[[rules]]` are added when no rule with the same `id` exists.
+pub fn merge(existing: &str, incoming: &str) -> Result<String> {
+    let mut doc: DocumentMut = existing
+        .parse()
+        .with_context(|| format!("Existing {} is not valid TOML", CONFIG_FILE))?;
+    let incoming: DocumentMut = incoming.parse().context("New configuration is not valid TOML")?;
+
+    merge_table(doc.as_table_mut(), incoming.as_table());
+    Ok(doc.to_string())
+}
+
+fn merge_table(existing: &mut Table, incoming: &Table) {
+    for (key, item) in incoming.iter() {
+        match existing.get_mut(key) {
+            Some(current) if !current.is_none() => merge_item(current, item),
+            _ => {
+                existing.insert(key, item.clone());
+            }
+        }
+    }
+}
+
+fn merge_item(current: &mut Item, incoming: &Item) {
+    match (current, incoming) {
+        (Item::Table(current), Item::Table(incoming)) => merge_table(current, incoming),
+        (Item::Value(Value::Array(current)), Item::Value(Value::Array(incoming))) => {
+            for value in incoming.iter() {
+                let present = current.iter().any(|v| same_value(v, value));
+                if !present {
+                    current.push_formatted(value.clone());
+                }
+            }
+        }
+        (Item::ArrayOfTables(current), Item::ArrayOfTables(incoming)) => {
+            for table in incoming.iter() {
+                let Some(id) = table.get("id").and_then(|v| v.as_str()) else {
+                    continue;
+                };
+                let present = current
+                    .iter()
+                    .any(|t| t.get("id").and_then(|v| v.as_str()) == Some(id));
+                if !present {
+                    current.push(table.clone());
+                }
+            }
+        }
+        // Scalars and anything shaped differently keep the existing value
+        _ => {}
+    }
+}
+
+/// Compare array entries by value, so `'x'`, `"x"` and `'''x'''` count as the same string
+fn same_value(a: &Value, b: &Value) -> bool {
+    match (a.as_str(), b.as_str()) {
+        (Some(a), Some(b)) => a == b,
+        _ => a.to_string().trim() == b.to_string().trim(),
+    }
+}
+
+fn parse_document(content: &str) -> Result<DocumentMut> {
+    content
+        .parse()
+        .with_context(|| format!("{} is not valid TOML", CONFIG_FILE))
+}
+
+/// Add an entry to `[allowlist]`, creating the table and array as needed
+///
+/// Comments and formatting are preserved and new entries follow the layout of the existing
+/// ones. Returns `None` when the entry is already present.
+pub fn add_allow(content: &str, kind: AllowKind, entry: &str) -> Result<Option<String>> {
+    let mut doc = parse_document(content)?;
+    let allowlist = doc
+        .entry("allowlist")
+        .or_insert(toml_edit::table())
+        .as_table_like_mut()
+        .context("[allowlist] must be a table")?;
+
+    if allowlist.get(kind.key()).is_none() {
+        allowlist.insert(kind.key(), toml_edit::value(toml_edit::Array::new()));
+    }
+    let array = allowlist
+        .get_mut(kind.key())
+        .and_then(Item::as_array_mut)
+        .with_context(|| format!("allowlist {} must be an array", kind.key()))?;
+
+    if array.iter().any(|v| v.as_str() == Some(entry)) {
+        return Ok(None);
+    }
+
+    let mut value = match kind {
+        // Patterns are written as literal strings so backslashes need no escaping
+        AllowKind::Path | AllowKind::Regex if !entry.contains("'''") => format!("'''{}'''", entry)
+            .parse::<Value>()
+            .unwrap_or_else(|_| Value::from(entry)),
+        _ => Value::from(entry),
+    };
+    match array.iter().last().and_then(|last| last.decor().prefix()).cloned() {
+        Some(prefix) => {
+            value.decor_mut().set_prefix(prefix);
+            array.push_formatted(value);
+        }
+        None => array.push(value),
+    }
+    Ok(Some(doc.to_string()))
+}
+
+/// Remove an entry from `[allowlist]`, from one array or all of them
+///
+/// Returns the kinds it was removed from, together with the updated config.
+pub fn remove_allow(content: &str, kind: Option<AllowKind>, entry: &str) -> Result<(Vec<AllowKind>, String)> {
+    let mut doc = parse_document(content)?;
+    let mut removed = Vec::new();
+
+    if let Some(allowlist) = doc.get_mut("allowlist").and_then(Item::as_table_like_mut) {
+        for candidate in AllowKind::ALL {
+            if kind.is_some_and(|k| k != candidate) {
+                continue;
+            }
+            if let Some(array) = allowlist.get_mut(candidate.key()).and_then(Item::as_array_mut) {
+                let before = array.len();
+                array.retain(|v| v.as_str() != Some(entry));
+                if array.len() != before {
+                    removed.push(candidate);
+                }
+            }
+        }
+    }
+    Ok((removed, doc.to_string()))
+}
+
+/// Entries of `[allowlist]`, in `paths`, `regexes`, `commits` order
+pub fn allow_entries(content: &str) -> Result<Vec<(AllowKind, String)>> {
+    let doc = parse_document(content)?;
+    let mut entries = Vec::new();
+    if let Some(allowlist) = doc.get("allowlist").and_then(Item::as_table_like) {
+        for kind in AllowKind::ALL {
+            if let Some(array) = allowlist.get(kind.key()).and_then(Item::as_array) {
+                entries.extend(array.iter().filter_map(Value::as_str).map(|v| (kind, v.to_string())));
+            }
+        }
+    }
+    Ok(entries)
+}
+
+/// Point `[extend] path` at `base`, creating the `[extend]` table at the top if needed
+///
+/// gitleaks rejects a config that sets both `path` and `useDefault`, so `useDefault` is dropped
+/// and the base config decides whether the default rules apply. Returns the updated config and
+/// whether `useDefault` was removed.
+pub fn set_extend_path(content: &str, base: &str) -> Result<(String, bool)> {
+    let mut doc = parse_document(content)?;
+    if !doc.contains_key("extend") {
+        let mut extend = Table::new();
+        extend.set_position(0);
+        doc.insert("extend", Item::Table(extend));
+    }
+    let extend = doc
+        .get_mut("extend")
+        .and_then(Item::as_table_like_mut)
+        .context("[extend] must be a table")?;
+
+    extend.insert("path", toml_edit::value(base));
+    let removed_default = extend.remove("useDefault").is_some();
+    Ok((doc.to_string(), removed_default))
+}
+
+/// A `[[rules]]` entry to append to a config
+#[derive(Debug, Default)]
+pub struct NewRule {
+    pub id: String,
+    pub description: String,
+    pub regex: String,
+    pub keywords: Vec<String>,
+    pub entropy: Option<f64>,
+    pub secret_group: Option<u32>,
+    pub tags: Vec<String>,
+}
+
+impl NewRule {
+    /// Render the rule as a `[[rules]]` block in the layout of the bundled configs
+    pub fn to_toml(&self) -> String {
+        let mut block = String::from("[[rules]]\n");
+        block.push_str(&format!("id = {}\n", Value::from(self.id.as_str())));
+        if !self.description.is_empty() {
+            block.push_str(&format!("description = {}\n", Value::from(self.description.as_str())));
+        }
+        block.push_str(&format!("regex = {}\n", pattern_literal(&self.regex)));
+        if let Some(group) = self.secret_group {
+            block.push_str(&format!("secretGroup = {}\n", group));
+        }
+        if let Some(entropy) = self.entropy {
+            block.push_str(&format!("entropy = {}\n", Value::from(entropy)));
+        }
+        if !self.keywords.is_empty() {
+            block.push_str(&format!("keywords = {}\n", string_array(&self.keywords)));
+        }
+        if !self.tags.is_empty() {
+            block.push_str(&format!("tags = {}\n", string_array(&self.tags)));
+        }
+        block
+    }
+}
+
+/// Append `rule` as a new `[[rules]]` block at the end of the config
+///
+/// The rest of the file is left as is. Fails if a rule with the same id already exists.
+pub fn add_rule(content: &str, rule: &NewRule) -> Result<String> {
+    let doc = parse_document(content)?;
+    if let Some(rules) = doc.get("rules") {
+        let rules = rules
+            .as_array_of_tables()
+            .context("rules must be declared as [[rules]] tables")?;
+        if rules.iter().any(|r| r.get("id").and_then(Item::as_str) == Some(rule.id.as_str())) {
+            anyhow::bail!("A rule with id '{}' already exists", rule.id);
+        }
+    }
+
+    let mut updated = content.trim_end().to_string();
+    if !updated.is_empty() {
+        updated.push_str("\n\n");
+    }
+    updated.push_str(&rule.to_toml());
+    parse_document(&updated).context("The new rule would make the config invalid")?;
+    Ok(updated)
+}
+
+/// Render a regex as a `'''` literal string, or a basic string where a literal can't hold it
+fn pattern_literal(pattern: &str) -> String {
+    if pattern.contains("'''") || pattern.contains('\n') || pattern.ends_with('\'') {
+        Value::from(pattern).to_string()
+    } else {
+        format!("'''{}'''", pattern)
+    }
+}
+
+fn string_array(values: &[String]) -> String {
+    let array: toml_edit::Array = values.iter().map(String::as_str).collect();
+    array.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merges_without_overriding_existing_settings() {
+        let existing = r#"title = "Ours"
+
+[allowlist]
+paths = ['''vendor/''']
+
+[[rules]]
+id = "custom"
+regex = '''ours'''
+"#;
+        let incoming = r#"title = "Theirs"
+
+[extend]
+useDefault = true
+
+[allowlist]
+description = "Generated files"
+paths = ['''vendor/''', '''\.lock$''']
+
+[[rules]]
+id = "custom"
+regex = '''theirs'''
+
+[[rules]]
+id = "aws-access-key"
+regex = '''AKIA[A-Z0-9]{16}'''
+"#;
+
+        let merged: toml::Table = toml::from_str(&merge(existing, incoming).unwrap()).unwrap();
+        assert_eq!(merged["title"].as_str(), Some("Ours"));
+        assert_eq!(merged["extend"]["useDefault"].as_bool(), Some(true));
+        assert_eq!(merged["allowlist"]["description"].as_str(), Some("Generated files"));
+
+        let paths: Vec<_> = merged["allowlist"]["paths"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|p| p.as_str().unwrap())
+            .collect();
+        assert_eq!(paths, vec!["vendor/", "\\.lock$"]);
+
+        let rules = merged["rules"].as_array().unwrap();
+        assert_eq!(rules.len(), 2);
+        assert_eq!(rules[0]["regex"].as_str(), Some("ours"));
+        assert_eq!(rules[1]["id"].as_str(), Some("aws-access-key"));
+    }
+
+    #[test]
+    fn merge_ignores_quoting_differences() {
+        let existing = "[allowlist]\npaths = ['''vendor/''', \"docs/\"]\n";
+        let incoming = "[allowlist]\npaths = [\"vendor/\", 'docs/', '''build/''']\n";
+
+        let merged: toml::Table = toml::from_str(&merge(existing, incoming).unwrap()).unwrap();
+        let paths: Vec<_> = merged["allowlist"]["paths"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|p| p.as_str().unwrap())
+            .collect();
+        assert_eq!(paths, vec!["vendor/", "docs/", "build/"]);
+    }
+
+    #[test]
+    fn edits_allowlist_preserving_layout() {
+        let config = r#"# Team config
+title = "Ours"
+
+[allowlist]
+# Generated files
+paths = [
+    '''vendor/''',
+]
+"#;
+        let added = add_allow(config, AllowKind::Path, r"\.lock$").unwrap().unwrap();
+        assert!(added.starts_with("# Team config\n"));
+        assert!(added.contains("# Generated files\npaths = [\n    '''vendor/''',\n    '''\\.lock$''',\n]"));
+        assert!(add_allow(&added, AllowKind::Path, "vendor/").unwrap().is_none());
+
+        let added = add_allow(&added, AllowKind::Commit, "abc1234").unwrap().unwrap();
+        assert_eq!(
+            allow_entries(&added).unwrap(),
+            vec![
+                (AllowKind::Path, "vendor/".to_string()),
+                (AllowKind::Path, r"\.lock$".to_string()),
+                (AllowKind::Commit, "abc1234".to_string()),
+            ]
+        );
+
+        let (removed, updated) = remove_allow(&added, None, "vendor/").unwrap();
+        assert_eq!(removed, vec![AllowKind::Path]);
+        assert_eq!(allow_entries(&updated).unwrap().len(), 2);
+        assert!(remove_allow(&updated, None, "missing").unwrap().0.is_empty());
+    }
+
+    #[test]
+    fn creates_allowlist_when_missing() {
+        let added = add_allow("title = \"x\"\n", AllowKind::Regex, "EXAMPLE").unwrap().unwrap();
+        let parsed: toml::Table = toml::from_str(&added).unwrap();
+        assert_eq!(parsed["allowlist"]["regexes"][0].as_str(), Some("EXAMPLE"));
+    }
+
+    #[test]
+    fn appends_rules_as_blocks() {
+        let config = "title = \"Ours\"\n\n[allowlist]\npaths = ['''vendor/''']\n";
+        let rule = NewRule {
+            id: "acme-token".to_string(),
+            description: "Acme API token".to_string(),
+            regex: r"acme_(?:live|test)_([a-z0-9]{32})".to_string(),
+            keywords: vec!["acme_".to_string()],
+            entropy: Some(3.5),
+            secret_group: Some(1),
+            tags: Vec::new(),
+        };
+
+        let added = add_rule(config, &rule).unwrap();
+        assert!(added.starts_with(config));
+        assert!(added.ends_with(
+            "\n\n[[rules]]\nid = \"acme-token\"\ndescription = \"Acme API token\"\n\
+             regex = '''acme_(?:live|test)_([a-z0-9]{32})'''\nsecretGroup = 1\nentropy = 3.5\n\
+             keywords = [\"acme_\"]\n"
+        ));
+        let parsed: toml::Table = toml::from_str(&added).unwrap();
+        assert_eq!(parsed["rules"][0]["regex"].as_str(), Some(rule.regex.as_str()));
+        assert_eq!(parsed["allowlist"]["paths"][0].as_str(), Some("vendor/"));
+
+        assert!(add_rule(&added, &rule).is_err());
+    }
+
+    #[test]
+    fn points_extend_at_base_config() {
+        let (updated, removed_default) =
+            set_extend_path("title = \"Ours\"\n\n[extend]\nuseDefault = true\n", ".git/base.toml").unwrap();
+        assert!(removed_default);
+        assert_eq!(updated, "title = \"Ours\"\n\n[extend]\npath = \".git/base.toml\"\n");
+
+        let config = "title = \"Ours\"\n\n[allowlist]\npaths = [\"vendor/\"]\n";
+        let (updated, removed_default) = set_extend_path(config, ".git/base.toml").unwrap();
+        assert!(!removed_default);
+        let parsed: toml::Table = toml::from_str(&updated).unwrap();
+        assert_eq!(parsed["extend"]["path"].as_str(), Some(".git/base.toml"));
+        assert!(updated.find("[extend]").unwrap() < updated.find("[allowlist]").unwrap());
+    }
+}
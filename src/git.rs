@@ -1,4 +1,5 @@
 use anyhow::{Context, Result};
+use std::path::PathBuf;
 use std::process::Command;
 
 /// Enable gitleaks by setting hooks.gitleaks-enable to true
@@ -31,20 +32,46 @@ pub fn disable_gitleaks() -> Result<()> {
     Ok(())
 }
 
-/// Check if gitleaks is enabled
-pub fn is_gitleaks_enabled() -> Result<bool> {
+/// Check if hooks.gitleaks-enable is set at all
+pub fn has_gitleaks_config() -> bool {
+    Command::new("git")
+        .args(["config", "--local", "--get", "hooks.gitleaks-enable"])
+        .output()
+        .map(|output| output.status.success())
+        .unwrap_or(false)
+}
+
+/// Remove hooks.gitleaks-enable from the repository config
+pub fn unset_gitleaks_config() -> Result<()> {
     let output = Command::new("git")
-        .args(["config", "--bool", "hooks.gitleaks-enable"])
+        .args(["config", "--local", "--unset", "hooks.gitleaks-enable"])
         .output()
-        .context("Failed to check gitleaks status")?;
+        .context("Failed to remove gitleaks config")?;
 
-    if !output.status.success() {
-        // Config not set, default to false
-        return Ok(false);
+    // Exit code 5 means the key was not set, which is what we want anyway
+    if !output.status.success() && output.status.code() != Some(5) {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!("Failed to remove gitleaks config: {}", stderr);
     }
 
-    let result = String::from_utf8_lossy(&output.stdout);
-    Ok(result.trim() == "true")
+    Ok(())
+}
+
+/// Identify the current git user as `Name <email>`, or whichever of the two is configured
+pub fn user_identity() -> Option<String> {
+    let get = |key: &str| {
+        Command::new("git")
+            .args(["config", "--get", key])
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+            .filter(|value| !value.is_empty())
+    };
+    match (get("user.name"), get("user.email")) {
+        (Some(name), Some(email)) => Some(format!("{} <{}>", name, email)),
+        (name, email) => name.or(email),
+    }
 }
 
 /// Clone a repository
@@ -62,6 +89,67 @@ pub fn clone_repository(url: &str, dest: &str) -> Result<()> {
     Ok(())
 }
 
+/// Top-level directory of the current working tree
+pub fn toplevel() -> Result<PathBuf> {
+    let output = Command::new("git")
+        .args(["rev-parse", "--show-toplevel"])
+        .output()
+        .context("Failed to locate the working tree")?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!("Failed to locate the working tree: {}", stderr);
+    }
+
+    Ok(PathBuf::from(String::from_utf8_lossy(&output.stdout).trim()))
+}
+
+/// Resolve a path inside the repository's git directory, relative to the current directory
+///
+/// In a linked worktree git answers with an absolute path.
+pub fn git_path(path: &str) -> Result<PathBuf> {
+    let output = Command::new("git")
+        .args(["rev-parse", "--git-path", path])
+        .output()
+        .context("Failed to locate the git directory")?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!("Failed to locate the git directory: {}", stderr);
+    }
+
+    Ok(PathBuf::from(String::from_utf8_lossy(&output.stdout).trim()))
+}
+
+/// Read a single file from a remote repository at `reference` (a branch, tag or commit)
+///
+/// Only that commit is fetched, into a throwaway repository. Returns the file contents and the
+/// commit they were read from.
+pub fn fetch_file(repo: &str, reference: &str, path: &str) -> Result<(String, String)> {
+    let dir = tempfile::tempdir().context("Failed to create temporary directory")?;
+    let git = |args: &[&str]| -> Result<String> {
+        let output = Command::new("git")
+            .arg("-C")
+            .arg(dir.path())
+            .args(args)
+            .output()
+            .context("Failed to run git")?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            anyhow::bail!("git {} failed: {}", args[0], stderr.trim());
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    };
+
+    git(&["init", "--quiet"])?;
+    git(&["fetch", "--quiet", "--depth", "1", repo, reference])
+        .with_context(|| format!("Failed to fetch {} from {}", reference, repo))?;
+    let content = git(&["show", &format!("FETCH_HEAD:{}", path)])
+        .with_context(|| format!("{} not found in {} at {}", path, repo, reference))?;
+    let commit = git(&["rev-parse", "FETCH_HEAD"])?.trim().to_string();
+    Ok((content, commit))
+}
+
 /// Check if current directory is a git repository
 pub fn is_git_repo() -> bool {
     Command::new("git")
@@ -70,12 +158,3 @@ pub fn is_git_repo() -> bool {
         .map(|output| output.status.success())
         .unwrap_or(false)
 }
-
-/// Restore a file to its committed state
-pub fn restore_file(file: &str) -> Result<()> {
-    Command::new("git")
-        .args(["restore", file])
-        .output()
-        .context("Failed to restore file")?;
-    Ok(())
-}
@@ -1,192 +1,629 @@
 use anyhow::{Context, Result};
-use colored::*;
 use flate2::read::GzDecoder;
-use indicatif::{ProgressBar, ProgressStyle};
-use reqwest::blocking::Client;
-use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
 use std::fs::{self, File};
-use std::io::{BufReader, Write};
-use std::path::Path;
+use std::io::{self, BufReader};
+use std::path::{Path, PathBuf};
 use std::process::Command;
 use tar::Archive;
 
-#[cfg(windows)]
-use std::io::Read;
-#[cfg(windows)]
 use zip::ZipArchive;
 
-use crate::utils;
+use crate::cache::ArchiveCache;
+use crate::templates::Template;
+use crate::versions::VersionStore;
+use crate::{config, lint, release, utils};
 
-#[derive(Debug, Deserialize, Serialize)]
-struct GithubAsset {
-    name: String,
-    browser_download_url: String,
+#[cfg(windows)]
+pub const BINARY_NAME: &str = "gitleaks.exe";
+#[cfg(not(windows))]
+pub const BINARY_NAME: &str = "gitleaks";
+
+/// Where the gitleaks release archive comes from
+#[derive(Debug, Clone, Default)]
+pub enum ArchiveSource {
+    /// Download from the GitHub releases API
+    #[default]
+    GitHub,
+    /// A single release archive already on disk
+    Archive(PathBuf),
+    /// A directory mirroring release assets (archives plus `gitleaks_<version>_checksums.txt`)
+    Mirror(PathBuf),
 }
 
-#[derive(Debug, Deserialize, Serialize)]
-struct GithubRelease {
-    tag_name: String,
-    assets: Vec<GithubAsset>,
+/// Which gitleaks release to install and where to put it
+#[derive(Debug, Default)]
+pub struct InstallOptions {
+    /// Release tag to install (e.g. `v8.18.0` or `8.18.0`); `None` installs the latest release
+    pub version: Option<String>,
+    /// Directory the gitleaks binary is written to
+    pub install_dir: PathBuf,
+    /// Known-good archive digests keyed by asset name, e.g. from the repository lockfile
+    pub pinned_checksums: BTreeMap<String, String>,
+    /// Where the release archive is obtained from
+    pub source: ArchiveSource,
 }
 
-/// Download and install gitleaks
-pub fn install_gitleaks(os: &str, arch: &str) -> Result<()> {
-    utils::print_info("Fetching latest gitleaks release...");
-
-    let client = Client::new();
-    let url = "https://api.github.com/repos/gitleaks/gitleaks/releases/latest";
-
-    let response = client
-        .get(url)
-        .header("User-Agent", "gitleaks-guard")
-        .send()
-        .context("Failed to fetch gitleaks releases")?;
-
-    let release: GithubRelease = response.json().context("Failed to parse release data")?;
+/// Result of a successful gitleaks installation
+#[derive(Debug)]
+pub struct InstalledGitleaks {
+    /// Path of the installed binary
+    pub path: PathBuf,
+    /// Release tag that was installed
+    pub tag: String,
+    /// Every archive digest published with the release, keyed by asset name
+    pub checksums: BTreeMap<String, String>,
+}
 
-    // Find the appropriate asset
-    let platform_str = get_platform_string(os, arch);
-    let version_str = release.tag_name.trim_start_matches('v');
-    let asset_pattern = format!("gitleaks_{}_{}", version_str, platform_str);
+/// A release archive on local disk, ready to be verified and extracted
+struct FetchedArchive {
+    path: PathBuf,
+    name: String,
+    /// Release tag, when it can be determined before extraction
+    tag: Option<String>,
+    /// Digests from the release checksums file, empty if none was found
+    checksums: BTreeMap<String, String>,
+    /// Whether the archive came out of the download cache
+    cached: bool,
+}
 
-    let asset = release
-        .assets
-        .iter()
-        .find(|a| a.name.ends_with(&platform_str) && a.name.contains(&format!("gitleaks_{}", version_str)))
-        .context(format!("No asset found for {} {} (looking for pattern: {})", os, arch, asset_pattern))?;
+/// Install gitleaks according to `options`
+pub fn install_gitleaks(os: &str, arch: &str, options: &InstallOptions) -> Result<InstalledGitleaks> {
+    let platform_str = get_platform_string(os, arch)?;
+    let temp_dir = tempfile::tempdir().context("Failed to create temp directory")?;
+    let version = options.version.as_deref();
+
+    let fetched = match &options.source {
+        ArchiveSource::GitHub => {
+            match cached_pinned_archive(version, &platform_str, &options.pinned_checksums) {
+                Some(fetched) => fetched,
+                None => download_release(os, arch, version, temp_dir.path())?,
+            }
+        }
+        ArchiveSource::Mirror(dir) => find_in_mirror(dir, &platform_str, version)?,
+        ArchiveSource::Archive(path) => local_archive(path)?,
+    };
 
-    utils::print_info(&format!("Downloading gitleaks {}...", release.tag_name));
+    if let (Some(requested), Some(tag)) = (version, &fetched.tag) {
+        if normalize_tag(requested) != normalize_tag(tag) {
+            anyhow::bail!(
+                "{} contains gitleaks {}, but {} is required",
+                fetched.name,
+                normalize_tag(tag),
+                normalize_tag(requested)
+            );
+        }
+    }
 
-    // Download the file
-    let response = client
-        .get(&asset.browser_download_url)
-        .send()
-        .context("Failed to download gitleaks")?;
+    // Verify the archive against the release checksums before touching anything
+    utils::print_info("Verifying checksum...");
+    let expected = fetched.checksums.get(&fetched.name);
+    if let Some(expected) = expected {
+        verify_checksum(&fetched.path, expected)?;
+    }
 
-    let total_size = response.content_length().unwrap_or(0);
-    let pb = ProgressBar::new(total_size);
-    pb.set_style(
-        ProgressStyle::default_bar()
-            .template("{spinner:.green} [{bar:40.cyan/blue}] {bytes}/{total_bytes} ({eta})")
-            .expect("Failed to create progress bar")
-            .progress_chars("#>-"),
-    );
+    // A pinned digest must agree too, otherwise the release itself changed under us
+    let pinned = pinned_checksum(&fetched, &options.pinned_checksums)?;
+    if let Some(pinned) = &pinned {
+        verify_checksum(&fetched.path, pinned)
+            .context("Archive does not match the checksum pinned in the lockfile")?;
+    }
 
-    let temp_dir = tempfile::tempdir().context("Failed to create temp directory")?;
-    let download_path = temp_dir.path().join(&asset.name);
-    let mut file = File::create(&download_path).context("Failed to create download file")?;
+    let mut checksums = fetched.checksums.clone();
+    if expected.is_none() && pinned.is_none() {
+        if !matches!(options.source, ArchiveSource::Archive(_)) {
+            anyhow::bail!(
+                "No checksum listed for {}; refusing to install unverified binary",
+                fetched.name
+            );
+        }
+        let actual = sha256_file(&fetched.path)?;
+        utils::print_warning(&format!(
+            "No checksums file found next to {}; installing unverified archive (sha256 {})",
+            fetched.name, actual
+        ));
+        checksums.insert(fetched.name.clone(), actual);
+    } else {
+        utils::print_success("Checksum verified");
+    }
 
-    let content = response.bytes().context("Failed to read download content")?;
-    file.write_all(&content).context("Failed to write download")?;
-    pb.finish_with_message("Download complete");
+    // Keep verified downloads so reinstalls and updates don't fetch them again
+    if matches!(options.source, ArchiveSource::GitHub) && !fetched.cached {
+        if let Some(digest) = checksums.get(&fetched.name) {
+            let stored = ArchiveCache::open().and_then(|c| c.store(&fetched.path, digest, &fetched.name));
+            if let Err(e) = stored {
+                utils::print_warning(&format!("Could not cache {}: {}", fetched.name, e));
+            }
+        }
+    }
 
     // Extract the archive
     utils::print_info("Extracting gitleaks...");
     let extract_path = temp_dir.path().join("extracted");
-    fs::create_dir_all(&extract_path).context("Failed to create extraction directory")?;
+    extract_archive(&fetched.path, &extract_path)?;
 
-    #[cfg(not(windows))]
+    let binary_path = extract_path.join(BINARY_NAME);
+
+    // Verify binary exists after extraction
+    if !binary_path.exists() {
+        anyhow::bail!("Binary not found after extraction: {}", binary_path.display());
+    }
+
+    // Run it once before replacing anything, so a wrong-architecture binary never gets installed
+    #[cfg(unix)]
     {
-        let tar_gz = File::open(&download_path).context("Failed to open downloaded file")?;
-        let tar = GzDecoder::new(BufReader::new(tar_gz));
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&binary_path, fs::Permissions::from_mode(0o755))
+            .context("Failed to make gitleaks executable")?;
+    }
+    let reported_version = get_version_at(&binary_path).with_context(|| {
+        format!(
+            "The downloaded gitleaks binary ({}) does not run on this host ({} {})",
+            fetched.name, os, arch
+        )
+    })?;
+
+    // Archives without a version in their name are checked against what the binary reports
+    let tag = match &fetched.tag {
+        Some(tag) => normalize_tag(tag),
+        None => {
+            let reported = parse_version(&reported_version).with_context(|| {
+                format!("Could not determine the gitleaks version of {}", fetched.name)
+            })?;
+            let tag = normalize_tag(&reported.to_string());
+            if let Some(requested) = version {
+                if normalize_tag(requested) != tag {
+                    anyhow::bail!(
+                        "{} contains gitleaks {}, but {} is required",
+                        fetched.name,
+                        tag,
+                        normalize_tag(requested)
+                    );
+                }
+            }
+            tag
+        }
+    };
+
+    let install_path = install_binary(&binary_path, &tag, &options.install_dir)?;
+
+    utils::print_success("Gitleaks installed successfully!");
+    Ok(InstalledGitleaks {
+        path: install_path,
+        tag,
+        checksums,
+    })
+}
+
+/// Digest pinned in the lockfile for `fetched`, if the lockfile pins any
+///
+/// Archives are looked up by asset name first. A local archive whose name does not carry a
+/// release tag is recognized by its content instead, and one matching no pinned digest is
+/// refused.
+fn pinned_checksum(fetched: &FetchedArchive, pinned: &BTreeMap<String, String>) -> Result<Option<String>> {
+    if let Some(digest) = pinned.get(&fetched.name) {
+        return Ok(Some(digest.clone()));
+    }
+    if pinned.is_empty() || fetched.tag.is_some() {
+        return Ok(None);
+    }
+
+    let actual = sha256_file(&fetched.path)?;
+    if pinned.values().any(|digest| digest.eq_ignore_ascii_case(&actual)) {
+        Ok(Some(actual))
+    } else {
+        anyhow::bail!(
+            "{} (sha256 {}) does not match any archive pinned in the lockfile",
+            fetched.name,
+            actual
+        )
+    }
+}
+
+/// Download the release archive and its checksums into `dest_dir`
+fn download_release(
+    os: &str,
+    arch: &str,
+    version: Option<&str>,
+    dest_dir: &Path,
+) -> Result<FetchedArchive> {
+    let api_url = release::gitleaks_api_url()?;
+    release::blocking(|| {
+        let client = release::client()?;
+        let tag = version.map(normalize_tag);
+        match &tag {
+            Some(tag) => utils::print_info(&format!("Fetching gitleaks release {}...", tag)),
+            None => utils::print_info("Fetching latest gitleaks release..."),
+        }
+        let release = release::fetch_release(&client, &api_url, tag.as_deref())?;
+
+        // Find the appropriate asset
+        let platform_str = get_platform_string(os, arch)?;
+        let expected_name = asset_name(&release.tag_name, &platform_str);
+        let asset = release.asset(&expected_name).context(format!(
+            "No asset found for {} {} in release {} (looking for: {})",
+            os, arch, release.tag_name, expected_name
+        ))?;
+
+        let checksums_asset = release.checksums_asset().context(format!(
+            "Release {} does not publish a checksums file; refusing to install unverified binary",
+            release.tag_name
+        ))?;
+        let checksums = release::fetch_text(&client, &checksums_asset.browser_download_url)
+            .context("Failed to download release checksums")?;
+        let checksums = parse_checksum_entries(&checksums);
+
+        // Skip the download entirely when this exact archive is already cached
+        let cached = checksums
+            .get(&asset.name)
+            .and_then(|digest| ArchiveCache::open().ok()?.lookup(digest, &asset.name));
+        if let Some(path) = cached {
+            utils::print_info(&format!("Using cached gitleaks {}", release.tag_name));
+            return Ok(FetchedArchive {
+                path,
+                name: asset.name.clone(),
+                tag: Some(release.tag_name.clone()),
+                checksums,
+                cached: true,
+            });
+        }
+
+        utils::print_info(&format!("Downloading gitleaks {}...", release.tag_name));
+
+        // Download the file
+        let pb = release::progress_bar();
+        let download_path = dest_dir.join(&asset.name);
+        release::download_file(&client, &asset.browser_download_url, &download_path, &pb)
+            .context("Failed to download gitleaks")?;
+        pb.finish_with_message("Download complete");
+
+        Ok(FetchedArchive {
+            path: download_path,
+            name: asset.name.clone(),
+            tag: Some(release.tag_name.clone()),
+            checksums,
+            cached: false,
+        })
+    })
+}
+
+/// Use a cached archive for a version whose digest is already pinned, without any network access
+fn cached_pinned_archive(
+    version: Option<&str>,
+    platform_str: &str,
+    pinned_checksums: &BTreeMap<String, String>,
+) -> Option<FetchedArchive> {
+    let tag = normalize_tag(version?);
+    let name = asset_name(&tag, platform_str);
+    let digest = pinned_checksums.get(&name)?;
+    let path = ArchiveCache::open().ok()?.lookup(digest, &name)?;
+
+    utils::print_info(&format!("Using cached gitleaks {}", tag));
+    Some(FetchedArchive {
+        path,
+        name,
+        tag: Some(tag),
+        checksums: pinned_checksums.clone(),
+        cached: true,
+    })
+}
+
+/// Locate the archive for this platform in a mirror directory
+///
+/// With no pinned version, the highest version present in the mirror is used.
+fn find_in_mirror(dir: &Path, platform_str: &str, version: Option<&str>) -> Result<FetchedArchive> {
+    if !dir.is_dir() {
+        anyhow::bail!("Mirror directory does not exist: {}", dir.display());
+    }
+
+    let tag = match version {
+        Some(v) => normalize_tag(v),
+        None => {
+            let entries = fs::read_dir(dir)
+                .with_context(|| format!("Failed to read mirror directory {}", dir.display()))?;
+            entries
+                .filter_map(|entry| entry.ok())
+                .filter_map(|entry| {
+                    let name = entry.file_name().to_string_lossy().to_string();
+                    name.strip_suffix(&format!("_{}", platform_str))?
+                        .strip_prefix("gitleaks_")
+                        .and_then(|v| semver::Version::parse(v).ok())
+                })
+                .max()
+                .map(|v| format!("v{}", v))
+                .context(format!(
+                    "No gitleaks archive for {} found in {}",
+                    platform_str,
+                    dir.display()
+                ))?
+        }
+    };
+
+    let name = asset_name(&tag, platform_str);
+    let path = dir.join(&name);
+    if !path.exists() {
+        anyhow::bail!("{} not found in mirror {}", name, dir.display());
+    }
+    utils::print_info(&format!("Using gitleaks {} from {}", tag, dir.display()));
+
+    Ok(FetchedArchive {
+        path,
+        name,
+        checksums: read_local_checksums(dir, &tag),
+        tag: Some(tag),
+        cached: false,
+    })
+}
+
+/// Use a release archive the user already has on disk
+fn local_archive(path: &Path) -> Result<FetchedArchive> {
+    if !path.is_file() {
+        anyhow::bail!("Archive does not exist: {}", path.display());
+    }
+
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .context(format!("Invalid archive path: {}", path.display()))?;
+    let tag = tag_from_asset_name(&name);
+    let checksums = match (&tag, path.parent()) {
+        (Some(tag), Some(dir)) => read_local_checksums(dir, tag),
+        _ => BTreeMap::new(),
+    };
+    utils::print_info(&format!("Using local archive {}", path.display()));
+
+    Ok(FetchedArchive {
+        path: path.to_path_buf(),
+        name,
+        tag,
+        checksums,
+        cached: false,
+    })
+}
+
+/// Read `gitleaks_<version>_checksums.txt` from a local directory, if present
+fn read_local_checksums(dir: &Path, tag: &str) -> BTreeMap<String, String> {
+    let file = dir.join(format!("gitleaks_{}_checksums.txt", tag.trim_start_matches('v')));
+    fs::read_to_string(file)
+        .map(|content| parse_checksum_entries(&content))
+        .unwrap_or_default()
+}
+
+/// Recover the release tag from an asset name like `gitleaks_8.18.0_linux_x64.tar.gz`
+fn tag_from_asset_name(name: &str) -> Option<String> {
+    let version = name.strip_prefix("gitleaks_")?.split('_').next()?;
+    semver::Version::parse(version).ok()?;
+    Some(format!("v{}", version))
+}
+
+/// Unpack a `.tar.gz` or `.zip` release archive into `dest`
+pub fn extract_archive(archive_path: &Path, dest: &Path) -> Result<()> {
+    fs::create_dir_all(dest).context("Failed to create extraction directory")?;
+    let file = File::open(archive_path).context("Failed to open downloaded file")?;
+
+    if archive_path.extension().is_some_and(|ext| ext == "zip") {
+        let mut archive = ZipArchive::new(file).context("Failed to open zip archive")?;
+        archive.extract(dest).context("Failed to extract zip")?;
+    } else {
+        let tar = GzDecoder::new(BufReader::new(file));
         let mut archive = Archive::new(tar);
-        archive.unpack(&extract_path).context("Failed to extract archive")?;
+        archive.unpack(dest).context("Failed to extract archive")?;
     }
+    Ok(())
+}
 
+/// Default directory gitleaks is installed into when `--install-dir` is not given
+///
+/// Root (and Windows) keeps the system-wide location; everyone else gets `~/.local/bin`
+/// so installation works without sudo.
+pub fn default_install_dir() -> Result<PathBuf> {
     #[cfg(windows)]
     {
-        let file = File::open(&download_path).context("Failed to open downloaded file")?;
-        let mut archive = ZipArchive::new(file).context("Failed to open zip archive")?;
-        archive.extract(&extract_path).context("Failed to extract zip")?;
+        let program_files =
+            std::env::var("ProgramFiles").unwrap_or_else(|_| "C:\\Program Files".to_string());
+        Ok(PathBuf::from(program_files).join("gitleaks"))
     }
 
-    // Determine binary name and install path
-    #[cfg(windows)]
-    let binary_name = "gitleaks.exe";
     #[cfg(not(windows))]
-    let binary_name = "gitleaks";
-
-    let binary_path = extract_path.join(binary_name);
-    
-    // Verify binary exists after extraction
-    if !binary_path.exists() {
-        anyhow::bail!("Binary not found after extraction: {}", binary_path.display());
+    {
+        if utils::is_root() {
+            return Ok(PathBuf::from("/usr/local/bin"));
+        }
+        let home = std::env::var("HOME").context("HOME is not set; pass --install-dir")?;
+        Ok(PathBuf::from(home).join(".local").join("bin"))
     }
+}
 
-    #[cfg(target_os = "linux")]
-    let install_path = "/usr/local/bin/gitleaks";
-    #[cfg(target_os = "macos")]
-    let install_path = "/usr/local/bin/gitleaks";
-    #[cfg(target_os = "windows")]
-    let install_path = {
-        let program_files = std::env::var("ProgramFiles")
-            .unwrap_or_else(|_| "C:\\Program Files".to_string());
-        format!("{}\\gitleaks\\gitleaks.exe", program_files)
+/// Directory `update`, `use` and `update --rollback` target when `--install-dir` is not given
+///
+/// This is wherever the gitleaks on PATH lives, so the switch replaces the binary git hooks
+/// actually run. Only when gitleaks is not installed does it fall back to
+/// [`default_install_dir`].
+pub fn active_install_dir() -> Result<PathBuf> {
+    let Some(dir) = path_install_dir() else {
+        return default_install_dir();
     };
 
-    // Install based on platform
-    #[cfg(not(windows))]
-    {
-        utils::print_info("Installing gitleaks to /usr/local/bin...");
+    if !utils::is_writable_dir(&dir) {
+        anyhow::bail!(
+            "gitleaks is installed in {}, which is not writable; rerun with sudo or pass --install-dir",
+            dir.display()
+        );
+    }
+    Ok(dir)
+}
 
-        // Try with sudo if needed
-        let output = Command::new("sudo")
-            .args(["mv", binary_path.to_str().unwrap(), install_path])
-            .output()
-            .context("Failed to install gitleaks")?;
+/// Directory holding the gitleaks found on PATH, if any
+pub fn path_install_dir() -> Option<PathBuf> {
+    utils::find_command("gitleaks").and_then(|path| path.parent().map(Path::to_path_buf))
+}
 
-        if !output.status.success() {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            anyhow::bail!("Failed to install gitleaks: {}", stderr);
-        }
+/// Copy an extracted gitleaks binary into `install_dir` and make it executable
+fn install_binary(binary_path: &Path, tag: &str, install_dir: &Path) -> Result<PathBuf> {
+    utils::print_info(&format!("Installing gitleaks to {}...", install_dir.display()));
 
-        // Make it executable
-        let chmod_output = Command::new("sudo")
-            .args(["chmod", "+x", install_path])
-            .output()
-            .context("Failed to make gitleaks executable")?;
-        
-        if !chmod_output.status.success() {
-            let stderr = String::from_utf8_lossy(&chmod_output.stderr);
-            anyhow::bail!("Failed to make gitleaks executable: {}", stderr);
-        }
+    // Keep every release side by side and point the install directory at the new one
+    let store = VersionStore::for_install_dir(install_dir)?;
+    store.add(tag, binary_path)?;
+    let install_path = store.activate(tag, install_dir)?;
+
+    warn_if_not_in_path(install_dir);
+    warn_if_shadowed(&install_path);
+    Ok(install_path)
+}
+
+/// Check whether `binary` is the gitleaks that PATH (and therefore git hooks) resolves to
+pub fn is_active_binary(binary: &Path) -> bool {
+    let Some(active) = utils::find_command("gitleaks") else {
+        return false;
+    };
+    match (active.canonicalize(), binary.canonicalize()) {
+        (Ok(active), Ok(binary)) => active == binary,
+        _ => active == binary,
     }
+}
 
-    #[cfg(windows)]
-    {
-        utils::print_info("Installing gitleaks to Program Files...");
+/// The gitleaks on PATH that would keep running after installing into `install_dir`, if any
+pub fn shadowing_binary(install_dir: &Path) -> Option<PathBuf> {
+    let active = utils::find_command("gitleaks")?;
+    let same_dir = active.parent().is_some_and(|dir| {
+        match (dir.canonicalize(), install_dir.canonicalize()) {
+            (Ok(dir), Ok(install_dir)) => dir == install_dir,
+            _ => dir == install_dir,
+        }
+    });
+    (!same_dir).then_some(active)
+}
 
-        let install_dir = Path::new(&install_path).parent().unwrap();
-        fs::create_dir_all(install_dir).context("Failed to create installation directory")?;
+/// Fail before switching versions when another gitleaks would keep running instead
+pub fn ensure_not_shadowed(install_dir: &Path) -> Result<()> {
+    let Some(active) = shadowing_binary(install_dir) else {
+        return Ok(());
+    };
+    utils::print_error(&format!(
+        "{} comes first in your PATH, so a gitleaks installed to {} would not be used.",
+        active.display(),
+        install_dir.display()
+    ));
+    utils::print_info("Omit --install-dir to switch that binary, or reorder PATH.");
+    anyhow::bail!("gitleaks in {} would be shadowed by {}", install_dir.display(), active.display())
+}
 
-        fs::copy(&binary_path, &install_path)
-            .context("Failed to copy gitleaks.exe. You may need to run as Administrator.")?;
+/// Warn when another gitleaks earlier in PATH would run instead of `binary`
+///
+/// Returns `true` when `binary` is the active one.
+pub fn warn_if_shadowed(binary: &Path) -> bool {
+    if is_active_binary(binary) {
+        return true;
+    }
+    if let Some(active) = utils::find_command("gitleaks") {
+        utils::print_warning(&format!(
+            "{} is shadowed by {} earlier in your PATH.",
+            binary.display(),
+            active.display()
+        ));
+        utils::print_info("Git hooks will keep running that binary until it is removed or PATH is reordered.");
+    }
+    false
+}
 
-        utils::print_warning("Please ensure the installation directory is in your PATH:");
-        utils::print_info(&format!("  {}", install_dir.display()));
+/// Tell the user how to put `install_dir` on PATH so git hooks can find gitleaks
+pub fn warn_if_not_in_path(install_dir: &Path) {
+    if !utils::is_in_path(install_dir) {
+        utils::print_warning(&format!("{} is not in your PATH.", install_dir.display()));
+        utils::print_info("Add it to your shell profile so git hooks can find gitleaks:");
+        #[cfg(windows)]
+        utils::print_info(&format!("  setx PATH \"%PATH%;{}\"", install_dir.display()));
+        #[cfg(not(windows))]
+        utils::print_info(&format!("  export PATH=\"{}:$PATH\"", install_dir.display()));
     }
+}
 
-    utils::print_success("Gitleaks installed successfully!");
+/// Pull a semantic version out of `gitleaks version` output such as `v8.18.0` or `gitleaks version 8.18.0`
+pub fn parse_version(output: &str) -> Option<semver::Version> {
+    output
+        .split_whitespace()
+        .map(|word| word.trim_start_matches('v'))
+        .find(|word| word.starts_with(|c: char| c.is_ascii_digit()))
+        .and_then(|word| semver::Version::parse(word).ok())
+}
+
+/// Normalize a user-supplied version into a gitleaks release tag (`8.18.0` -> `v8.18.0`)
+pub fn normalize_tag(version: &str) -> String {
+    format!("v{}", version.trim().trim_start_matches('v'))
+}
+
+/// Expected archive name for a release tag and platform string
+fn asset_name(tag: &str, platform_str: &str) -> String {
+    format!("gitleaks_{}_{}", tag.trim_start_matches('v'), platform_str)
+}
+
+/// Collect every `digest  file` entry of a checksums file into a map keyed by file name
+pub fn parse_checksum_entries(checksums: &str) -> BTreeMap<String, String> {
+    checksums
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.split_whitespace();
+            let digest = parts.next()?;
+            let name = parts.next()?.trim_start_matches('*');
+            Some((name.to_string(), digest.to_lowercase()))
+        })
+        .collect()
+}
+
+/// Compute the hex-encoded SHA-256 digest of a file
+pub fn sha256_file(path: &Path) -> Result<String> {
+    let mut file = File::open(path)
+        .with_context(|| format!("Failed to open {} for hashing", path.display()))?;
+    let mut hasher = Sha256::new();
+    io::copy(&mut file, &mut hasher)
+        .with_context(|| format!("Failed to hash {}", path.display()))?;
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Verify that a downloaded file matches the expected SHA-256 digest
+pub fn verify_checksum(path: &Path, expected: &str) -> Result<()> {
+    let actual = sha256_file(path)?;
+    if !actual.eq_ignore_ascii_case(expected) {
+        anyhow::bail!(
+            "Checksum mismatch for {}\n  expected: {}\n  actual:   {}",
+            path.display(),
+            expected,
+            actual
+        );
+    }
     Ok(())
 }
 
 /// Get platform string for gitleaks download
-fn get_platform_string(os: &str, arch: &str) -> String {
-    match (os, arch) {
-        ("linux", "x64") => "linux_x64.tar.gz",
-        ("linux", "arm64") => "linux_arm64.tar.gz",
-        ("darwin", "x64") => "darwin_x64.tar.gz",
-        ("darwin", "arm64") => "darwin_arm64.tar.gz",
-        ("windows", "x64") => "windows_x64.zip",
-        ("windows", "arm64") => "windows_arm64.zip",
-        _ => "linux_x64.tar.gz", // default fallback
-    }
-    .to_string()
+///
+/// Only combinations gitleaks actually publishes are accepted; anything else is an error rather
+/// than a binary that cannot execute here.
+fn get_platform_string(os: &str, arch: &str) -> Result<String> {
+    let platform = match (os, arch) {
+        ("linux", "x64" | "x32" | "arm64" | "armv6" | "armv7") => format!("linux_{}.tar.gz", arch),
+        ("darwin", "x64" | "arm64") => format!("darwin_{}.tar.gz", arch),
+        ("windows", "x64" | "x32" | "arm64" | "armv6" | "armv7") => format!("windows_{}.zip", arch),
+        _ => anyhow::bail!(
+            "gitleaks does not publish binaries for {} {}. Supported platforms: \
+             linux (x64, x32, arm64, armv6, armv7), darwin (x64, arm64), \
+             windows (x64, x32, arm64, armv6, armv7)",
+            os,
+            arch
+        ),
+    };
+    Ok(platform)
 }
 
 /// Get gitleaks version
 pub fn get_version() -> Result<String> {
-    let output = Command::new("gitleaks")
+    get_version_at(Path::new("gitleaks"))
+}
+
+/// Get the version of a specific gitleaks binary
+pub fn get_version_at(binary: &Path) -> Result<String> {
+    let output = Command::new(binary)
         .arg("version")
         .output()
         .context("Failed to get gitleaks version")?;
@@ -198,92 +635,96 @@ pub fn get_version() -> Result<String> {
     Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
 }
 
-/// Create gitleaks configuration file
-pub fn create_config() -> Result<()> {
-    utils::print_info("Creating .gitleaks.toml configuration...");
+/// Default configuration embedded in the binary; extends the rules built into gitleaks
+const BUNDLED_CONFIG: &str = include_str!("../config/gitleaks.toml");
 
-    // Download the default config from gitleaks repository
-    let client = Client::new();
-    let config_url = "https://raw.githubusercontent.com/gitleaks/gitleaks/master/config/gitleaks.toml";
+/// Embedded configuration for gitleaks releases that cannot extend the built-in rules
+const BUNDLED_LEGACY_CONFIG: &str = include_str!("../config/gitleaks-legacy.toml");
 
-    let response = client
-        .get(config_url)
-        .send()
-        .context("Failed to download gitleaks config")?;
+/// First gitleaks release supporting `[extend] useDefault`
+const EXTEND_MIN_VERSION: semver::Version = semver::Version::new(8, 6, 0);
+
+/// Where the upstream default config is fetched from with `--fetch-latest-config`
+const LATEST_CONFIG_URL: &str = "https://raw.githubusercontent.com/gitleaks/gitleaks/master/config/gitleaks.toml";
 
-    let default_config = response.text().context("Failed to read config")?;
+/// Create gitleaks configuration file
+///
+/// Writes the bundled config matching the installed gitleaks version; the upstream config from
+/// the gitleaks `master` branch is only downloaded when `fetch_latest` is set.
+pub fn create_config(fetch_latest: bool, force: bool) -> Result<()> {
+    if !fetch_latest {
+        return create_offline_config(None, force);
+    }
 
-    // Write config to file (using default gitleaks config which already includes all necessary rules)
-    fs::write(".gitleaks.toml", default_config).context("Failed to write config file")?;
+    utils::print_info("Creating .gitleaks.toml configuration...");
+    let latest_config = release::blocking(|| release::fetch_text(&release::client()?, LATEST_CONFIG_URL))
+        .context("Failed to download gitleaks config")?;
 
-    utils::print_success("Configuration file created!");
+    if config::write(&latest_config, force)? {
+        utils::print_success("Configuration file created from the latest upstream gitleaks config!");
+    }
     Ok(())
 }
 
-/// Create strict gitleaks configuration
-pub fn create_strict_config() -> Result<()> {
-    utils::print_info("Creating strict .gitleaks.toml configuration...");
-
-    let strict_config = r#"title = "Gitleaks Strict Configuration"
-
-[extend]
-useDefault = true
+/// Create gitleaks configuration without network access
+///
+/// Copies `gitleaks.toml` from a mirror directory when available; otherwise writes the bundled
+/// config matching the installed gitleaks version.
+pub fn create_offline_config(mirror_dir: Option<&Path>, force: bool) -> Result<()> {
+    utils::print_info("Creating .gitleaks.toml configuration...");
 
-[allowlist]
-description = "Strict mode - minimal allowlist"
-paths = [
-    '''go\.sum$''',
-    '''\.lock$''',
-]
-"#;
+    if let Some(mirrored) = mirror_dir.map(|dir| dir.join("gitleaks.toml")) {
+        if mirrored.exists() {
+            let content = fs::read_to_string(&mirrored)
+                .with_context(|| format!("Failed to read {}", mirrored.display()))?;
+            if config::write(&content, force)? {
+                utils::print_success(&format!("Configuration copied from {}", mirrored.display()));
+            }
+            return Ok(());
+        }
+    }
 
-    fs::write(".gitleaks.toml", strict_config).context("Failed to write config file")?;
-    utils::print_success("Strict configuration file created!");
+    let installed = get_version().ok();
+    if config::write(bundled_config(installed.as_deref()), force)? {
+        utils::print_success("Configuration file created (using gitleaks built-in rules)!");
+    }
     Ok(())
 }
 
-/// Create minimal gitleaks configuration
-pub fn create_minimal_config() -> Result<()> {
-    utils::print_info("Creating minimal .gitleaks.toml configuration...");
-
-    let minimal_config = r#"title = "Gitleaks Minimal Configuration"
-
-[[rules]]
-id = "generic-api-key"
-description = "Generic API Key"
-regex = '''(?i)(api[_-]?key|apikey)['"\\s]*[:=]['"\\s]*[a-z0-9_\-]{20,}'''
-
-[[rules]]
-id = "aws-access-key"
-description = "AWS Access Key"
-regex = '''(A3T[A-Z0-9]|AKIA|AGPA|AIDA|AROA|AIPA|ANPA|ANVA|ASIA)[A-Z0-9]{16}'''
-
-[[rules]]
-id = "aws-secret-key"
-description = "AWS Secret Key"
-regex = '''(?i)aws(.{0,20})?(?-i)['\"][0-9a-zA-Z\/+]{40}['\"]'''
-
-[[rules]]
-id = "github-pat"
-description = "GitHub Personal Access Token"
-regex = '''ghp_[0-9a-zA-Z]{36}'''
-
-[[rules]]
-id = "generic-private-key"
-description = "Private Key"
-regex = '''-----BEGIN (RSA|EC|DSA|OPENSSH) PRIVATE KEY-----'''
-
-[allowlist]
-description = "Common false positives"
-paths = [
-    '''(.*?)(jpg|gif|doc|pdf|bin|svg|socket)$''',
-    '''(go|py|js)\.sum$''',
-    '''(yarn|package-lock|Cargo)\.lock$''',
-]
-"#;
+/// Pick the bundled config for an installed `gitleaks version`; unknown versions get the default
+pub fn bundled_config(installed: Option<&str>) -> &'static str {
+    match installed.and_then(parse_version) {
+        Some(version) if version < EXTEND_MIN_VERSION => BUNDLED_LEGACY_CONFIG,
+        _ => BUNDLED_CONFIG,
+    }
+}
+
+/// Create gitleaks configuration from a template
+///
+/// Custom templates are checked before they are written, so a broken team template never
+/// replaces a working config.
+pub fn create_template_config(template: &Template, force: bool) -> Result<()> {
+    utils::print_info(&format!("Creating .gitleaks.toml from the {} template...", template.name));
+
+    let content = template.content(get_version().ok().as_deref())?;
+    if template.is_custom() {
+        let errors: Vec<_> = lint::validate(&content)
+            .into_iter()
+            .filter(|d| d.severity == lint::Severity::Error)
+            .collect();
+        if let Some(error) = errors.first() {
+            anyhow::bail!(
+                "Template '{}' is not a valid gitleaks config{}: {}",
+                template.name,
+                error.line.map(|l| format!(" (line {})", l)).unwrap_or_default(),
+                error.message
+            );
+        }
+    }
 
-    fs::write(".gitleaks.toml", minimal_config).context("Failed to write config file")?;
-    utils::print_success("Minimal configuration file created!");
+    if config::write(&content, force)? {
+        utils::print_success(&format!("Configuration file created from the {} template!", template.name));
+    }
     Ok(())
 }
 
@@ -320,11 +761,31 @@ pub fn is_installed() -> bool {
     utils::command_exists("gitleaks")
 }
 
+/// File name a pre-existing pre-commit hook is moved to before ours is written
+pub const HOOK_BACKUP_NAME: &str = "pre-commit.gitleaks-guard.bak";
+
+/// Check whether a pre-commit hook was written by gitleaks-guard
+pub fn is_our_hook(hook_file: &Path) -> bool {
+    fs::read_to_string(hook_file)
+        .map(|content| {
+            // Hooks from before the marker comment are recognized by the config key they read
+            content.contains("# Installed by gitleaks-guard")
+                || content.contains("git config --bool hooks.gitleaks-enable")
+        })
+        .unwrap_or(false)
+}
+
+/// Check whether a pre-commit hook exists that gitleaks-guard did not write
+fn is_foreign_hook(hook_file: &Path) -> bool {
+    hook_file.exists() && !is_our_hook(hook_file)
+}
+
 /// Create pre-commit hook script
 pub fn create_pre_commit_hook() -> Result<()> {
     utils::print_info("Creating pre-commit hook...");
 
     let hook_content = r#"#!/bin/bash
+# Installed by gitleaks-guard
 
 # Color codes for terminal output
 GREEN='\033[0;32m'
@@ -337,6 +798,17 @@ function is_gitleaks_enabled() {
     git config --bool hooks.gitleaks-enable
 }
 
+# Function to make sure a linked organization base config has been fetched
+function check_base_config() {
+    local base
+    base=$(grep -oE "[^\"' ]*gitleaks-guard/base-config\.toml" .gitleaks.toml 2>/dev/null | head -n 1)
+    if [[ -n "$base" && ! -f "$base" ]]; then
+        echo -e "${RED}.gitleaks.toml extends $base, which has not been fetched yet.${NC}"
+        echo -e "${RED}Run 'gitleaks-guard config sync --locked' and commit again.${NC}"
+        exit 1
+    fi
+}
+
 # Function to run Gitleaks and check for secrets
 function run_gitleaks() {
     echo -e "${GREEN}Running Gitleaks...${NC}"
@@ -356,17 +828,41 @@ function run_gitleaks() {
 
 # Main script execution
 if [[ "$(is_gitleaks_enabled)" == "true" ]]; then
+    check_base_config
     run_gitleaks
 else
     echo -e "${BLUE}Gitleaks is not enabled in the Git config.${NC}"
 fi
 "#;
 
-    let hooks_dir = Path::new(".git/hooks");
+    write_hook(Path::new(".git/hooks"), hook_content)?;
+
+    utils::print_success("Pre-commit hook created!");
+    Ok(())
+}
+
+/// Write `content` as the pre-commit hook in `hooks_dir`, backing up a foreign hook first
+fn write_hook(hooks_dir: &Path, content: &str) -> Result<()> {
     fs::create_dir_all(hooks_dir).context("Failed to create hooks directory")?;
 
     let hook_file = hooks_dir.join("pre-commit");
-    fs::write(&hook_file, hook_content).context("Failed to write pre-commit hook")?;
+
+    // Keep someone else's hook so `uninstall` can put it back, but never clobber an older backup
+    if is_foreign_hook(&hook_file) {
+        let backup = hooks_dir.join(HOOK_BACKUP_NAME);
+        if backup.exists() {
+            anyhow::bail!(
+                "{} was not written by gitleaks-guard and {} already holds an earlier backup; \
+                 move one of them aside and try again",
+                hook_file.display(),
+                backup.display()
+            );
+        }
+        fs::rename(&hook_file, &backup).context("Failed to back up existing pre-commit hook")?;
+        utils::print_info(&format!("Existing pre-commit hook backed up to {}", backup.display()));
+    }
+
+    fs::write(&hook_file, content).context("Failed to write pre-commit hook")?;
 
     // Make executable
     #[cfg(unix)]
@@ -376,7 +872,179 @@ fi
         perms.set_mode(0o755);
         fs::set_permissions(&hook_file, perms)?;
     }
-
-    utils::print_success("Pre-commit hook created!");
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    const ARCHIVE: &str = "gitleaks_8.18.0_linux_x64.tar.gz";
+
+    fn fixture(name: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures")
+            .join(name)
+    }
+
+    fn fixture_checksums() -> String {
+        fs::read_to_string(fixture("gitleaks_8.18.0_checksums.txt")).unwrap()
+    }
+
+    fn fixture_digest() -> String {
+        parse_checksum_entries(&fixture_checksums()).remove(ARCHIVE).unwrap()
+    }
+
+    #[test]
+    fn parses_version_output() {
+        assert_eq!(parse_version("v8.18.0\n"), Some(semver::Version::new(8, 18, 0)));
+        assert_eq!(parse_version("gitleaks version 8.18.0"), Some(semver::Version::new(8, 18, 0)));
+        assert_eq!(parse_version("dev"), None);
+    }
+
+    #[test]
+    fn picks_bundled_config_for_installed_version() {
+        assert!(bundled_config(Some("8.18.0")).contains("useDefault = true"));
+        assert!(bundled_config(Some("v8.5.2\n")).contains("[[rules]]"));
+        assert!(bundled_config(None).contains("useDefault = true"));
+        for config in [BUNDLED_CONFIG, BUNDLED_LEGACY_CONFIG] {
+            toml::from_str::<toml::Table>(config).unwrap();
+        }
+    }
+
+    #[test]
+    fn matches_renamed_archive_against_pinned_digests() {
+        let dir = tempfile::tempdir().unwrap();
+        let renamed = dir.path().join("gitleaks.tar.gz");
+        fs::copy(fixture(ARCHIVE), &renamed).unwrap();
+        let archive = local_archive(&renamed).unwrap();
+        assert!(archive.tag.is_none());
+
+        let mut pinned = BTreeMap::new();
+        pinned.insert(ARCHIVE.to_string(), fixture_digest());
+        assert_eq!(pinned_checksum(&archive, &pinned).unwrap(), Some(fixture_digest()));
+        assert_eq!(pinned_checksum(&archive, &BTreeMap::new()).unwrap(), None);
+
+        pinned.insert(ARCHIVE.to_string(), "0".repeat(64));
+        assert!(pinned_checksum(&archive, &pinned).is_err());
+    }
+
+    #[test]
+    fn normalizes_tags() {
+        assert_eq!(normalize_tag("8.18.0"), "v8.18.0");
+        assert_eq!(normalize_tag("v8.18.0"), "v8.18.0");
+        assert_eq!(normalize_tag(" 8.18.0\n"), "v8.18.0");
+    }
+
+    #[test]
+    fn builds_asset_name_for_pinned_tag() {
+        let platform = get_platform_string("linux", "x64").unwrap();
+        assert_eq!(asset_name("v8.18.0", &platform), ARCHIVE);
+        assert_eq!(asset_name("8.18.0", &platform), ARCHIVE);
+    }
+
+    #[test]
+    fn maps_published_platforms() {
+        assert_eq!(get_platform_string("linux", "armv7").unwrap(), "linux_armv7.tar.gz");
+        assert_eq!(get_platform_string("linux", "x32").unwrap(), "linux_x32.tar.gz");
+        assert_eq!(get_platform_string("windows", "x32").unwrap(), "windows_x32.zip");
+        assert_eq!(get_platform_string("darwin", "arm64").unwrap(), "darwin_arm64.tar.gz");
+    }
+
+    #[test]
+    fn rejects_unpublished_platforms() {
+        assert!(get_platform_string("darwin", "armv7").is_err());
+        assert!(get_platform_string("freebsd", "x64").is_err());
+        assert!(get_platform_string("linux", "riscv64").is_err());
+    }
+
+    #[test]
+    fn parses_checksum_entries() {
+        let entries = parse_checksum_entries(&fixture_checksums());
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[ARCHIVE].len(), 64);
+        assert!(!entries.contains_key("gitleaks_8.18.0_linux_arm64.tar.gz"));
+
+        let entries = parse_checksum_entries("ABCD *gitleaks.zip\n\n");
+        assert_eq!(entries["gitleaks.zip"], "abcd");
+    }
+
+    #[test]
+    fn verifies_matching_archive() {
+        verify_checksum(&fixture(ARCHIVE), &fixture_digest()).unwrap();
+    }
+
+    #[test]
+    fn parses_tag_from_asset_name() {
+        assert_eq!(tag_from_asset_name(ARCHIVE).as_deref(), Some("v8.18.0"));
+        assert_eq!(tag_from_asset_name("gitleaks_8.18.0_windows_x64.zip").as_deref(), Some("v8.18.0"));
+        assert!(tag_from_asset_name("gitleaks.tar.gz").is_none());
+        assert!(tag_from_asset_name("gitleaks_latest_linux_x64.tar.gz").is_none());
+    }
+
+    #[test]
+    fn finds_latest_archive_in_mirror() {
+        let fetched = find_in_mirror(&fixture(""), "linux_x64.tar.gz", None).unwrap();
+        assert_eq!(fetched.name, ARCHIVE);
+        assert_eq!(fetched.tag.as_deref(), Some("v8.18.0"));
+        assert!(fetched.checksums.contains_key(ARCHIVE));
+
+        assert!(find_in_mirror(&fixture(""), "linux_x64.tar.gz", Some("8.19.0")).is_err());
+        assert!(find_in_mirror(&fixture(""), "darwin_arm64.tar.gz", None).is_err());
+    }
+
+    #[test]
+    fn reads_checksums_next_to_local_archive() {
+        let fetched = local_archive(&fixture(ARCHIVE)).unwrap();
+        assert_eq!(fetched.tag.as_deref(), Some("v8.18.0"));
+        assert_eq!(
+            fetched.checksums.get(ARCHIVE),
+            Some(&fixture_digest())
+        );
+    }
+
+    #[test]
+    fn extracts_tar_gz_archive() {
+        let dir = tempfile::tempdir().unwrap();
+        extract_archive(&fixture(ARCHIVE), dir.path()).unwrap();
+        assert!(dir.path().join("gitleaks").is_file());
+    }
+
+    #[test]
+    fn rejects_tampered_archive() {
+        let expected = fixture_digest();
+
+        let dir = tempfile::tempdir().unwrap();
+        let tampered = dir.path().join(ARCHIVE);
+        let mut bytes = fs::read(fixture(ARCHIVE)).unwrap();
+        bytes.push(0);
+        fs::write(&tampered, bytes).unwrap();
+
+        let err = verify_checksum(&tampered, &expected).unwrap_err().to_string();
+        assert!(err.contains("Checksum mismatch"));
+        assert!(err.contains(&format!("expected: {}", expected)));
+        assert!(err.contains(&format!("actual:   {}", sha256_file(&tampered).unwrap())));
+    }
+
+    #[test]
+    fn never_overwrites_an_earlier_hook_backup() {
+        let dir = tempfile::tempdir().unwrap();
+        let hook = dir.path().join("pre-commit");
+        let backup = dir.path().join(HOOK_BACKUP_NAME);
+        let ours = "#!/bin/sh\n# Installed by gitleaks-guard\n";
+
+        fs::write(&hook, "#!/bin/sh\necho first\n").unwrap();
+        write_hook(dir.path(), ours).unwrap();
+        assert_eq!(fs::read_to_string(&backup).unwrap(), "#!/bin/sh\necho first\n");
+
+        // Rewriting our own hook leaves the backup alone
+        write_hook(dir.path(), ours).unwrap();
+        assert_eq!(fs::read_to_string(&backup).unwrap(), "#!/bin/sh\necho first\n");
+
+        fs::write(&hook, "#!/bin/sh\necho second\n").unwrap();
+        assert!(write_hook(dir.path(), ours).is_err());
+        assert_eq!(fs::read_to_string(&hook).unwrap(), "#!/bin/sh\necho second\n");
+        assert_eq!(fs::read_to_string(&backup).unwrap(), "#!/bin/sh\necho first\n");
+    }
+}
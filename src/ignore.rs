@@ -0,0 +1,166 @@
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::Path;
+
+/// Fingerprint ignore file read by gitleaks from the repository root
+pub const IGNORE_FILE: &str = ".gitleaksignore";
+
+/// Contents of a `.gitleaksignore`, kept line by line so hand-written parts survive edits
+///
+/// Entries added by gitleaks-guard are preceded by a comment with the justification, author
+/// and date:
+///
+/// ```text
+/// # Test fixture, not a real key (added by Jane <jane@example.com> on 2024-05-01)
+/// 3f2a9c1:tests/fixtures/aws.txt:aws-access-token:3
+/// ```
+#[derive(Debug, Default)]
+pub struct IgnoreFile {
+    lines: Vec<String>,
+}
+
+impl IgnoreFile {
+    pub fn parse(content: &str) -> Self {
+        IgnoreFile {
+            lines: content.lines().map(str::to_string).collect(),
+        }
+    }
+
+    /// Load `path`, treating a missing file as empty
+    pub fn load_from(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        Ok(Self::parse(&content))
+    }
+
+    pub fn save_to(&self, path: &Path) -> Result<()> {
+        fs::write(path, self.to_string()).with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    /// Fingerprints listed in the file
+    pub fn fingerprints(&self) -> impl Iterator<Item = &str> {
+        self.lines.iter().map(|l| l.trim()).filter(|l| is_entry(l))
+    }
+
+    pub fn contains(&self, fingerprint: &str) -> bool {
+        self.fingerprints().any(|f| f == fingerprint)
+    }
+
+    /// Append a fingerprint with its justification; returns `false` if it is already listed
+    pub fn add(&mut self, fingerprint: &str, justification: &str, author: &str, date: &str) -> bool {
+        if self.contains(fingerprint) {
+            return false;
+        }
+        if self.lines.last().is_some_and(|l| !l.trim().is_empty()) {
+            self.lines.push(String::new());
+        }
+        self.lines.push(format!(
+            "# {} (added by {} on {})",
+            justification.trim(),
+            author,
+            date
+        ));
+        self.lines.push(fingerprint.to_string());
+        true
+    }
+
+    /// Drop entries for which `keep` returns `false`, along with the annotation `add` wrote above them
+    ///
+    /// Returns the removed fingerprints.
+    pub fn prune(&mut self, keep: impl Fn(&str) -> bool) -> Vec<String> {
+        let mut removed = Vec::new();
+        let mut lines: Vec<String> = Vec::with_capacity(self.lines.len());
+
+        for line in self.lines.drain(..) {
+            let entry = line.trim();
+            if !is_entry(entry) || keep(entry) {
+                lines.push(line);
+                continue;
+            }
+            removed.push(entry.to_string());
+            // Only the annotation belongs to the entry; other comments are the user's
+            if lines.last().is_some_and(|l| is_annotation(l)) {
+                lines.pop();
+            }
+            // Don't leave two blank lines where an entry used to be
+            if lines.last().is_some_and(|l| l.trim().is_empty()) {
+                lines.pop();
+            }
+        }
+
+        while lines.first().is_some_and(|l| l.trim().is_empty()) {
+            lines.remove(0);
+        }
+        self.lines = lines;
+        removed
+    }
+}
+
+impl std::fmt::Display for IgnoreFile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for line in &self.lines {
+            writeln!(f, "{}", line)?;
+        }
+        Ok(())
+    }
+}
+
+fn is_entry(line: &str) -> bool {
+    !line.is_empty() && !line.starts_with('#')
+}
+
+/// Check whether a line is a `# <reason> (added by <author> on <date>)` annotation
+fn is_annotation(line: &str) -> bool {
+    let line = line.trim();
+    line.starts_with('#')
+        && line.ends_with(')')
+        && line
+            .rfind(" (added by ")
+            .is_some_and(|start| line[start..].contains(" on "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn adds_annotated_entries_once() {
+        let mut file = IgnoreFile::parse("# hand-written\nabc:a.txt:rule:1\n");
+        assert!(file.add("def:b.txt:rule:2", "Test fixture", "Jane <jane@example.com>", "2024-05-01"));
+        assert!(!file.add("abc:a.txt:rule:1", "again", "Jane", "2024-05-01"));
+
+        assert_eq!(
+            file.to_string(),
+            "# hand-written\nabc:a.txt:rule:1\n\n\
+             # Test fixture (added by Jane <jane@example.com> on 2024-05-01)\ndef:b.txt:rule:2\n"
+        );
+    }
+
+    #[test]
+    fn prunes_entries_with_their_comments() {
+        let mut file = IgnoreFile::parse(
+            "# first (added by Jane on 2024-05-01)\nabc:a.txt:rule:1\n\n\
+             # second (added by Jane on 2024-05-02)\ndef:b.txt:rule:2\n",
+        );
+        let removed = file.prune(|fp| fp != "abc:a.txt:rule:1");
+        assert_eq!(removed, vec!["abc:a.txt:rule:1"]);
+        assert_eq!(file.to_string(), "# second (added by Jane on 2024-05-02)\ndef:b.txt:rule:2\n");
+    }
+
+    #[test]
+    fn prune_keeps_user_comments() {
+        let mut file = IgnoreFile::parse(
+            "# Fixtures shared by the API tests\n# first (added by Jane on 2024-05-01)\nabc:a.txt:rule:1\n\
+             # Keep until the vendored SDK is upgraded\ndef:b.txt:rule:2\nghi:c.txt:rule:3\n",
+        );
+        let removed = file.prune(|fp| fp == "ghi:c.txt:rule:3");
+        assert_eq!(removed, vec!["abc:a.txt:rule:1", "def:b.txt:rule:2"]);
+        assert_eq!(
+            file.to_string(),
+            "# Fixtures shared by the API tests\n# Keep until the vendored SDK is upgraded\nghi:c.txt:rule:3\n"
+        );
+    }
+}
@@ -0,0 +1,370 @@
+use regex::Regex;
+use std::collections::HashMap;
+use std::ops::Range;
+use toml_edit::{ImDocument, Item, TableLike, Value};
+
+/// Keys gitleaks understands at the top level of its config
+const ROOT_KEYS: [&str; 6] = ["title", "description", "extend", "rules", "allowlist", "allowlists"];
+
+/// Keys of the `[extend]` table
+const EXTEND_KEYS: [&str; 4] = ["path", "url", "useDefault", "disabledRules"];
+
+/// Keys of a `[[rules]]` entry
+const RULE_KEYS: [&str; 12] = [
+    "id",
+    "description",
+    "regex",
+    "secretGroup",
+    "entropy",
+    "path",
+    "keywords",
+    "tags",
+    "allowlist",
+    "allowlists",
+    "skipReport",
+    "required",
+];
+
+/// Keys of an allowlist, global or per rule
+const ALLOWLIST_KEYS: [&str; 8] = [
+    "description",
+    "condition",
+    "commits",
+    "paths",
+    "regexTarget",
+    "regexes",
+    "stopwords",
+    "targetRules",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// A problem found in a gitleaks config
+#[derive(Debug)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    /// 1-based line in the config file, when known
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+/// Check a gitleaks config for problems gitleaks would only report at scan time
+///
+/// Beyond TOML syntax this checks that every rule has an id and a regex or path, that all rule
+/// and allowlist patterns compile, and warns on duplicate rule ids and keys gitleaks ignores.
+/// Patterns are compiled with Rust's `regex` crate, whose syntax closely follows Go's RE2.
+pub fn validate(content: &str) -> Vec<Diagnostic> {
+    let mut linter = Linter {
+        content,
+        diagnostics: Vec::new(),
+    };
+    match ImDocument::parse(content) {
+        Ok(doc) => linter.check_root(doc.as_table()),
+        Err(e) => {
+            let line = linter.line_of(e.span());
+            linter.error(line, format!("invalid TOML: {}", e.message()));
+        }
+    }
+    linter.diagnostics
+}
+
+struct Linter<'a> {
+    content: &'a str,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Linter<'_> {
+    fn line_of(&self, span: Option<Range<usize>>) -> Option<usize> {
+        let start = span?.start.min(self.content.len());
+        Some(self.content[..start].matches('\n').count() + 1)
+    }
+
+    fn error(&mut self, line: Option<usize>, message: String) {
+        self.diagnostics.push(Diagnostic {
+            severity: Severity::Error,
+            line,
+            message,
+        });
+    }
+
+    fn warning(&mut self, line: Option<usize>, message: String) {
+        self.diagnostics.push(Diagnostic {
+            severity: Severity::Warning,
+            line,
+            message,
+        });
+    }
+
+    /// Line of `key` within `table`, falling back to the line of its value
+    fn key_line(&self, table: &dyn TableLike, key: &str) -> Option<usize> {
+        let (key, item) = table.get_key_value(key)?;
+        self.line_of(key.span()).or_else(|| self.line_of(item.span()))
+    }
+
+    fn check_root(&mut self, root: &dyn TableLike) {
+        self.check_unknown_keys(root, &ROOT_KEYS, "top level");
+
+        if let Some(item) = root.get("extend") {
+            match item.as_table_like() {
+                Some(extend) => self.check_unknown_keys(extend, &EXTEND_KEYS, "[extend]"),
+                None => self.error(self.key_line(root, "extend"), "[extend] must be a table".to_string()),
+            }
+        }
+
+        match root.get("rules") {
+            None => {}
+            Some(Item::ArrayOfTables(rules)) => {
+                let mut seen: HashMap<String, Option<usize>> = HashMap::new();
+                for (index, rule) in rules.iter().enumerate() {
+                    self.check_rule(index + 1, rule, &mut seen);
+                }
+            }
+            Some(_) => self.error(
+                self.key_line(root, "rules"),
+                "rules must be declared as [[rules]] tables".to_string(),
+            ),
+        }
+
+        if let Some(item) = root.get("allowlist") {
+            match item.as_table_like() {
+                Some(allowlist) => self.check_allowlist(allowlist, "[allowlist]"),
+                None => self.error(self.key_line(root, "allowlist"), "[allowlist] must be a table".to_string()),
+            }
+        }
+        if let Some(Item::ArrayOfTables(allowlists)) = root.get("allowlists") {
+            for (index, allowlist) in allowlists.iter().enumerate() {
+                self.check_allowlist(allowlist, &format!("[[allowlists]] #{}", index + 1));
+            }
+        }
+    }
+
+    fn check_rule(&mut self, number: usize, rule: &toml_edit::Table, seen: &mut HashMap<String, Option<usize>>) {
+        let rule_line = self.line_of(rule.span());
+        let label = match rule.get("id").and_then(Item::as_str) {
+            Some(id) => {
+                let line = self.key_line(rule, "id").or(rule_line);
+                match seen.get(id) {
+                    Some(first) => self.warning(
+                        line,
+                        format!(
+                            "duplicate rule id '{}'{}",
+                            id,
+                            first.map(|l| format!(" (first defined on line {})", l)).unwrap_or_default()
+                        ),
+                    ),
+                    None => {
+                        seen.insert(id.to_string(), line);
+                    }
+                }
+                format!("rule '{}'", id)
+            }
+            None => {
+                let message = if rule.contains_key("id") {
+                    format!("rule #{}: id must be a string", number)
+                } else {
+                    format!("rule #{} has no id", number)
+                };
+                self.error(rule_line, message);
+                format!("rule #{}", number)
+            }
+        };
+
+        if !rule.contains_key("regex") && !rule.contains_key("path") {
+            self.error(rule_line, format!("{} needs a regex or a path", label));
+        }
+        for key in ["regex", "path"] {
+            if let Some(item) = rule.get(key) {
+                let line = self.key_line(rule, key);
+                match item.as_str() {
+                    Some(pattern) => self.check_pattern(line, pattern, &format!("{} {}", label, key)),
+                    None => self.error(line, format!("{}: {} must be a string", label, key)),
+                }
+            }
+        }
+
+        if let Some(item) = rule.get("secretGroup") {
+            if item.as_integer().is_none_or(|group| group < 0) {
+                self.error(
+                    self.key_line(rule, "secretGroup"),
+                    format!("{}: secretGroup must be a non-negative integer", label),
+                );
+            }
+        }
+        if let Some(item) = rule.get("entropy") {
+            if item.as_float().is_none() && item.as_integer().is_none() {
+                self.error(self.key_line(rule, "entropy"), format!("{}: entropy must be a number", label));
+            }
+        }
+        for key in ["keywords", "tags"] {
+            self.check_string_array(rule, key, &label);
+        }
+
+        self.check_unknown_keys(rule, &RULE_KEYS, &label);
+
+        if let Some(item) = rule.get("allowlist") {
+            match item.as_table_like() {
+                Some(allowlist) => self.check_allowlist(allowlist, &format!("{} allowlist", label)),
+                None => self.error(
+                    self.key_line(rule, "allowlist"),
+                    format!("{}: allowlist must be a table", label),
+                ),
+            }
+        }
+        if let Some(Item::ArrayOfTables(allowlists)) = rule.get("allowlists") {
+            for allowlist in allowlists.iter() {
+                self.check_allowlist(allowlist, &format!("{} allowlist", label));
+            }
+        }
+    }
+
+    fn check_allowlist(&mut self, allowlist: &dyn TableLike, label: &str) {
+        self.check_unknown_keys(allowlist, &ALLOWLIST_KEYS, label);
+
+        for key in ["paths", "regexes"] {
+            let line = self.key_line(allowlist, key);
+            for pattern in self.check_string_array(allowlist, key, label) {
+                self.check_pattern(line, &pattern, &format!("{} {}", label, key));
+            }
+        }
+        for key in ["commits", "stopwords", "targetRules"] {
+            self.check_string_array(allowlist, key, label);
+        }
+
+        if let Some(item) = allowlist.get("regexTarget") {
+            if !matches!(item.as_str(), Some("secret" | "match" | "line")) {
+                self.error(
+                    self.key_line(allowlist, "regexTarget"),
+                    format!("{}: regexTarget must be \"secret\", \"match\" or \"line\"", label),
+                );
+            }
+        }
+        if let Some(item) = allowlist.get("condition") {
+            if !matches!(item.as_str().map(str::to_uppercase).as_deref(), Some("OR" | "AND")) {
+                self.error(
+                    self.key_line(allowlist, "condition"),
+                    format!("{}: condition must be \"OR\" or \"AND\"", label),
+                );
+            }
+        }
+    }
+
+    /// Check `key` is an array of strings, returning its strings
+    fn check_string_array(&mut self, table: &dyn TableLike, key: &str, label: &str) -> Vec<String> {
+        let Some(item) = table.get(key) else {
+            return Vec::new();
+        };
+        let line = self.key_line(table, key);
+        match item.as_array() {
+            Some(array) if array.iter().all(|v| v.as_str().is_some()) => {
+                array.iter().filter_map(Value::as_str).map(str::to_string).collect()
+            }
+            _ => {
+                self.error(line, format!("{}: {} must be an array of strings", label, key));
+                Vec::new()
+            }
+        }
+    }
+
+    fn check_pattern(&mut self, line: Option<usize>, pattern: &str, label: &str) {
+        if let Err(e) = Regex::new(pattern) {
+            self.error(line, format!("{}: regex does not compile: {}", label, regex_error(&e)));
+        }
+    }
+
+    fn check_unknown_keys(&mut self, table: &dyn TableLike, known: &[&str], label: &str) {
+        let unknown: Vec<String> = table
+            .iter()
+            .map(|(key, _)| key.to_string())
+            .filter(|key| !known.contains(&key.as_str()))
+            .collect();
+        for key in unknown {
+            let line = self.key_line(table, &key);
+            self.warning(line, format!("{}: unknown key '{}' is ignored by gitleaks", label, key));
+        }
+    }
+}
+
+/// One-line reason a regex failed to compile
+pub fn regex_error(error: &regex::Error) -> String {
+    // The regex crate explains syntax errors over several lines; the last one says what is wrong
+    let text = error.to_string();
+    text.lines()
+        .rev()
+        .find_map(|l| l.trim().strip_prefix("error: "))
+        .unwrap_or(text.trim())
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn messages(content: &str) -> Vec<(Severity, Option<usize>, String)> {
+        validate(content)
+            .into_iter()
+            .map(|d| (d.severity, d.line, d.message))
+            .collect()
+    }
+
+    #[test]
+    fn accepts_bundled_configs() {
+        assert!(validate(include_str!("../config/gitleaks.toml")).is_empty());
+        assert!(validate(include_str!("../config/gitleaks-legacy.toml")).is_empty());
+    }
+
+    #[test]
+    fn reports_problems_with_line_numbers() {
+        let config = r#"title = "Test"
+colour = "red"
+
+[[rules]]
+id = "aws"
+regex = '''AKIA[A-Z0-9{16}'''
+
+[[rules]]
+description = "no id"
+regex = '''x'''
+
+[[rules]]
+id = "aws"
+path = '''\.env$'''
+secretgroup = 1
+
+[allowlist]
+paths = ['''(unclosed''']
+regexTarget = "everything"
+"#;
+        let found = messages(config);
+        let expect = |severity, line, text: &str| {
+            assert!(
+                found.iter().any(|(s, l, m)| *s == severity && *l == Some(line) && m.contains(text)),
+                "missing {:?} on line {} containing '{}' in {:#?}",
+                severity,
+                line,
+                text,
+                found
+            );
+        };
+
+        expect(Severity::Warning, 2, "unknown key 'colour'");
+        expect(Severity::Error, 6, "rule 'aws' regex: regex does not compile");
+        expect(Severity::Error, 8, "rule #2 has no id");
+        expect(Severity::Warning, 13, "duplicate rule id 'aws' (first defined on line 5)");
+        expect(Severity::Warning, 15, "unknown key 'secretgroup'");
+        expect(Severity::Error, 18, "[allowlist] paths: regex does not compile");
+        expect(Severity::Error, 19, "regexTarget must be");
+        assert_eq!(found.len(), 7);
+    }
+
+    #[test]
+    fn reports_toml_syntax_errors() {
+        let found = messages("title = \"Test\"\n[[rules]\nid = \"x\"\n");
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].0, Severity::Error);
+        assert_eq!(found[0].1, Some(2));
+    }
+}
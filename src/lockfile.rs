@@ -0,0 +1,138 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use crate::gitleaks;
+
+/// Repository-level lockfile pinning the gitleaks release and the organization base config,
+/// committed alongside `.gitleaks.toml`
+pub const LOCKFILE_NAME: &str = ".gitleaks-guard.toml";
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct Lockfile {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gitleaks: Option<GitleaksLock>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_config: Option<BaseConfigLock>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct GitleaksLock {
+    /// Required gitleaks version, without the leading `v`
+    pub version: String,
+    /// SHA-256 digests of the release archives, keyed by asset name
+    #[serde(default)]
+    pub checksums: BTreeMap<String, String>,
+}
+
+/// Organization base config that `.gitleaks.toml` extends, pinned by content hash
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct BaseConfigLock {
+    /// URL or `git+<repo>//<path>[@<ref>]` source the config is fetched from
+    pub source: String,
+    /// SHA-256 digest of the config contents
+    pub sha256: String,
+    /// Commit the config was read from, for git sources
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub commit: Option<String>,
+}
+
+impl GitleaksLock {
+    /// Pin a release tag and its published archive digests
+    pub fn new(tag: &str, checksums: BTreeMap<String, String>) -> Self {
+        GitleaksLock {
+            version: tag.trim_start_matches('v').to_string(),
+            checksums,
+        }
+    }
+
+    /// Check whether an installed `gitleaks version` string satisfies the lock
+    pub fn is_satisfied_by(&self, installed: &str) -> bool {
+        gitleaks::parse_version(installed)
+            .is_some_and(|installed| Some(installed) == gitleaks::parse_version(&self.version))
+    }
+}
+
+impl Lockfile {
+    /// Load the lockfile from the current directory, if one exists
+    pub fn load() -> Result<Option<Self>> {
+        Self::load_from(Path::new(LOCKFILE_NAME))
+    }
+
+    /// The gitleaks pin of the lockfile in the current directory, if any
+    pub fn gitleaks() -> Result<Option<GitleaksLock>> {
+        Ok(Self::load()?.and_then(|lock| lock.gitleaks))
+    }
+
+    /// Apply `change` to the lockfile in the current directory, creating it if needed
+    pub fn update(change: impl FnOnce(&mut Lockfile)) -> Result<()> {
+        let mut lock = Self::load()?.unwrap_or_default();
+        change(&mut lock);
+        lock.save()
+    }
+
+    /// Load a lockfile from `path`, if it exists
+    pub fn load_from(path: &Path) -> Result<Option<Self>> {
+        if !path.exists() {
+            return Ok(None);
+        }
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let lock = toml::from_str(&content)
+            .with_context(|| format!("Failed to parse {}", path.display()))?;
+        Ok(Some(lock))
+    }
+
+    /// Write the lockfile to the current directory
+    pub fn save(&self) -> Result<()> {
+        self.save_to(Path::new(LOCKFILE_NAME))
+    }
+
+    /// Write the lockfile to `path`
+    pub fn save_to(&self, path: &Path) -> Result<()> {
+        let body = toml::to_string_pretty(self).context("Failed to serialize lockfile")?;
+        let content = format!(
+            "# Managed by gitleaks-guard. Commit this file so everyone runs the same gitleaks setup.\n{}",
+            body
+        );
+        fs::write(path, content).with_context(|| format!("Failed to write {}", path.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_through_toml() {
+        let mut checksums = BTreeMap::new();
+        checksums.insert("gitleaks_8.18.0_linux_x64.tar.gz".to_string(), "abc".to_string());
+        let lock = Lockfile {
+            gitleaks: Some(GitleaksLock::new("v8.18.0", checksums.clone())),
+            base_config: None,
+        };
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(LOCKFILE_NAME);
+        lock.save_to(&path).unwrap();
+        assert!(!fs::read_to_string(&path).unwrap().contains("base_config"));
+
+        let loaded = Lockfile::load_from(&path).unwrap().unwrap();
+        let gitleaks = loaded.gitleaks.unwrap();
+        assert_eq!(gitleaks.version, "8.18.0");
+        assert_eq!(gitleaks.checksums, checksums);
+        assert!(loaded.base_config.is_none());
+        assert!(Lockfile::load_from(&dir.path().join("missing.toml")).unwrap().is_none());
+    }
+
+    #[test]
+    fn compares_installed_version() {
+        let lock = GitleaksLock::new("8.18.0", BTreeMap::new());
+        assert!(lock.is_satisfied_by("8.18.0\n"));
+        assert!(lock.is_satisfied_by("v8.18.0"));
+        assert!(lock.is_satisfied_by("gitleaks version 8.18.0"));
+        assert!(!lock.is_satisfied_by("8.19.0"));
+    }
+}
@@ -1,17 +1,36 @@
 use anyhow::Result;
 use clap::{Parser, Subcommand};
 use colored::*;
+use std::path::PathBuf;
 
+mod base_config;
+mod cache;
 mod commands;
+mod config;
 mod gitleaks;
 mod git;
+mod ignore;
+mod lint;
+mod lockfile;
+mod release;
+mod release_notes;
+mod report;
+mod rule_tests;
+mod settings;
+mod templates;
+mod update_check;
 mod utils;
+mod versions;
 
 /// GitLeaks Guard - Automated security pre-commit hook installer
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
 #[command(name = "gitleaks-guard")]
 struct Cli {
+    /// Don't print update-available notices
+    #[arg(short, long, global = true)]
+    quiet: bool,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -19,12 +38,49 @@ struct Cli {
 #[derive(Subcommand)]
 enum Commands {
     /// Interactive setup wizard (recommended for first-time setup)
-    Init,
+    Init {
+        /// Directory to install gitleaks into (defaults to ~/.local/bin when not root)
+        #[arg(long, value_name = "DIR")]
+        install_dir: Option<PathBuf>,
+        /// Config template to use instead of choosing one (see 'config templates')
+        #[arg(long, value_name = "NAME")]
+        template: Option<String>,
+        /// Download the latest upstream gitleaks config instead of using the bundled one
+        #[arg(long, conflicts_with = "template")]
+        fetch_latest_config: bool,
+        /// Overwrite an existing .gitleaks.toml without asking
+        #[arg(long)]
+        force: bool,
+    },
     /// Install gitleaks and setup pre-commit hooks
     Install {
         /// Skip gitleaks download if already installed
         #[arg(short, long)]
         skip_download: bool,
+        /// Install a specific gitleaks release instead of the latest (e.g. v8.18.0)
+        #[arg(long, value_name = "TAG")]
+        version: Option<String>,
+        /// Directory to install gitleaks into (defaults to ~/.local/bin when not root)
+        #[arg(long, value_name = "DIR")]
+        install_dir: Option<PathBuf>,
+        /// Pin the installed gitleaks version and checksums in .gitleaks-guard.toml
+        #[arg(long)]
+        lock: bool,
+        /// Install from a local gitleaks release archive instead of downloading (offline)
+        #[arg(long, value_name = "FILE", conflicts_with = "mirror_dir")]
+        from_archive: Option<PathBuf>,
+        /// Install from a directory mirroring gitleaks release assets (offline)
+        #[arg(long, value_name = "DIR")]
+        mirror_dir: Option<PathBuf>,
+        /// Create .gitleaks.toml from a template (see 'config templates')
+        #[arg(long, value_name = "NAME")]
+        template: Option<String>,
+        /// Download the latest upstream gitleaks config instead of using the bundled one
+        #[arg(long, conflicts_with_all = ["from_archive", "mirror_dir", "template"])]
+        fetch_latest_config: bool,
+        /// Overwrite an existing .gitleaks.toml without asking
+        #[arg(long)]
+        force: bool,
     },
     /// Show current status and configuration
     Status,
@@ -44,27 +100,267 @@ enum Commands {
         #[arg(short, long)]
         cleanup: bool,
     },
-    /// Update gitleaks to the latest version
+    /// Update gitleaks to the latest (or a pinned) version
     Update {
         /// Force reinstall even if already on latest version
         #[arg(short, long)]
         force: bool,
+        /// Update to a specific gitleaks release instead of the latest (e.g. v8.18.0)
+        #[arg(long, value_name = "TAG")]
+        version: Option<String>,
+        /// Directory to install gitleaks into (defaults to where the gitleaks on PATH lives)
+        #[arg(long, value_name = "DIR")]
+        install_dir: Option<PathBuf>,
+        /// Allow installing a version older than the one currently installed
+        #[arg(long)]
+        allow_downgrade: bool,
+        /// Switch back to the gitleaks version that was active before the last update
+        #[arg(long, conflicts_with_all = ["force", "version", "allow_downgrade"])]
+        rollback: bool,
+    },
+    /// Switch to an installed gitleaks version, or list installed versions
+    Use {
+        /// Version to activate (e.g. 8.18.0); omit to list installed versions
+        version: Option<String>,
+        /// Directory the active gitleaks is linked into (defaults to where the gitleaks on PATH lives)
+        #[arg(long, value_name = "DIR")]
+        install_dir: Option<PathBuf>,
     },
     /// Check gitleaks version
     Version,
+    /// Update gitleaks-guard itself to the latest release
+    SelfUpdate {
+        /// Reinstall even if already on the latest version
+        #[arg(short, long)]
+        force: bool,
+        /// Do not ask for confirmation
+        #[arg(short, long)]
+        yes: bool,
+    },
+    /// Remove what install/init set up (defaults to the repository setup only)
+    #[command(group(clap::ArgGroup::new("scope").args(["repo_only", "binary", "all"])))]
+    Uninstall {
+        /// Remove only the repository setup: config, lockfile, hook and git config
+        #[arg(long)]
+        repo_only: bool,
+        /// Remove only the gitleaks binary
+        #[arg(long)]
+        binary: bool,
+        /// Remove both the repository setup and the gitleaks binary
+        #[arg(long)]
+        all: bool,
+        /// List what would be removed without changing anything
+        #[arg(long)]
+        dry_run: bool,
+        /// Do not ask for confirmation
+        #[arg(short, long)]
+        yes: bool,
+    },
+    /// Manage the gitleaks download cache
+    Cache {
+        #[command(subcommand)]
+        action: CacheAction,
+    },
+    /// Inspect the repository's gitleaks configuration and its organization base config
+    Config {
+        #[command(subcommand)]
+        action: ConfigAction,
+    },
+    /// Manage the [allowlist] of .gitleaks.toml
+    Allow {
+        /// Config file to edit
+        #[arg(long, global = true, default_value = config::CONFIG_FILE)]
+        config: PathBuf,
+        #[command(subcommand)]
+        action: AllowAction,
+    },
+    /// Ignore specific findings by fingerprint in .gitleaksignore
+    #[command(args_conflicts_with_subcommands = true)]
+    Ignore {
+        /// Finding fingerprints ([commit:]file:rule:line)
+        fingerprints: Vec<String>,
+        /// Take fingerprints from a gitleaks JSON report (e.g. gitleaks-report.json)
+        #[arg(long, value_name = "FILE")]
+        from_report: Option<PathBuf>,
+        /// Only take findings of this rule from the report
+        #[arg(long, value_name = "ID", requires = "from_report")]
+        rule: Option<String>,
+        /// Why the findings are safe to ignore (prompted for when omitted)
+        #[arg(short, long)]
+        reason: Option<String>,
+        #[command(subcommand)]
+        action: Option<IgnoreAction>,
+    },
+    /// Work with gitleaks rules
+    Rules {
+        #[command(subcommand)]
+        action: RulesAction,
+    },
+    /// Refresh the cached update check (run in the background by other commands)
+    #[command(hide = true)]
+    RefreshUpdateCheck,
+}
+
+#[derive(Subcommand)]
+enum CacheAction {
+    /// List cached gitleaks archives
+    List,
+    /// Remove all cached gitleaks archives
+    Clean,
+}
+
+#[derive(Subcommand)]
+enum ConfigAction {
+    /// Check .gitleaks.toml for syntax errors, invalid regexes and unknown keys
+    Validate {
+        /// Config file to check
+        #[arg(default_value = config::CONFIG_FILE)]
+        path: PathBuf,
+    },
+    /// Extend .gitleaks.toml from an organization base config, pinned by hash
+    Link {
+        /// https:// URL, or git+<repo>//<path>[@<ref>]
+        source: String,
+    },
+    /// List the templates init and install can create .gitleaks.toml from
+    Templates,
+    /// Verify the cached base config and refresh it from its source
+    Sync {
+        /// Only restore the pinned version; fail if the source has changed
+        #[arg(long)]
+        locked: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum AllowAction {
+    /// Ignore files whose path matches a regex
+    Path {
+        /// Path regex (e.g. '^docs/')
+        regex: String,
+    },
+    /// Ignore secrets matching a regex
+    Regex {
+        /// Secret regex
+        pattern: String,
+    },
+    /// Ignore findings introduced by a commit
+    Commit {
+        /// Commit SHA
+        sha: String,
+    },
+    /// List allowlist entries
+    List,
+    /// Remove an allowlist entry
+    Remove {
+        /// Entry exactly as listed by 'allow list'
+        entry: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum IgnoreAction {
+    /// Remove entries that no longer match any finding
+    Prune {
+        /// Show what would be removed without changing anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum RulesAction {
+    /// Check rules against positive and negative sample strings
+    Test {
+        /// Samples file with `positive`/`negative` strings per rule id
+        #[arg(default_value = rule_tests::FIXTURES_FILE)]
+        fixtures: PathBuf,
+        /// Gitleaks config whose rules are tested
+        #[arg(long, default_value = config::CONFIG_FILE)]
+        config: PathBuf,
+    },
+    /// Append a custom rule to .gitleaks.toml (prompts for anything not given as a flag)
+    Add {
+        /// Rule id (e.g. acme-api-token)
+        #[arg(long)]
+        id: Option<String>,
+        /// Human-readable description
+        #[arg(long)]
+        description: Option<String>,
+        /// Regex matching the secret
+        #[arg(long)]
+        regex: Option<String>,
+        /// Keyword that must appear for the rule to run (repeatable)
+        #[arg(long = "keyword", value_name = "KEYWORD")]
+        keywords: Vec<String>,
+        /// Minimum Shannon entropy of the secret
+        #[arg(long)]
+        entropy: Option<f64>,
+        /// Capture group of the regex holding the secret
+        #[arg(long)]
+        secret_group: Option<u32>,
+        /// Tag attached to findings (repeatable)
+        #[arg(long = "tag", value_name = "TAG")]
+        tags: Vec<String>,
+        /// Text the rule must report (repeatable)
+        #[arg(long = "sample", value_name = "TEXT")]
+        samples: Vec<String>,
+        /// Config file to add the rule to
+        #[arg(long, default_value = config::CONFIG_FILE)]
+        config: PathBuf,
+    },
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
     let cli = Cli::parse();
 
+    // Commands that already report versions, or run unattended, skip the update notice
+    let notify = !cli.quiet
+        && !matches!(
+            cli.command,
+            Commands::Update { .. } | Commands::SelfUpdate { .. } | Commands::RefreshUpdateCheck
+        );
+
     match cli.command {
-        Commands::Init => {
-            commands::init::run().await?;
+        Commands::Init {
+            install_dir,
+            template,
+            fetch_latest_config,
+            force,
+        } => {
+            commands::init::run(install_dir, template, fetch_latest_config, force).await?;
         }
-        Commands::Install { skip_download } => {
+        Commands::Install {
+            skip_download,
+            version,
+            install_dir,
+            lock,
+            from_archive,
+            mirror_dir,
+            template,
+            fetch_latest_config,
+            force,
+        } => {
             println!("{}", "🔒 GitLeaks Guard - Installation".bold().blue());
-            commands::install::run(skip_download).await?;
+            let source = match (from_archive, mirror_dir) {
+                (Some(archive), _) => gitleaks::ArchiveSource::Archive(archive),
+                (None, Some(dir)) => gitleaks::ArchiveSource::Mirror(dir),
+                (None, None) => gitleaks::ArchiveSource::GitHub,
+            };
+            commands::install::run(
+                skip_download,
+                version,
+                install_dir,
+                lock,
+                source,
+                commands::install::ConfigOptions {
+                    template,
+                    fetch_latest: fetch_latest_config,
+                    force,
+                },
+            )
+            .await?;
         }
         Commands::Status => {
             commands::status::run()?;
@@ -81,12 +377,107 @@ async fn main() -> Result<()> {
             println!("{}", "🕵️‍♂️ GitLeaks Scanner".bold().blue());
             commands::scan::run(url, path, cleanup).await?;
         }
-        Commands::Update { force } => {
-            commands::update::run(force).await?;
+        Commands::Update {
+            force,
+            version,
+            install_dir,
+            allow_downgrade,
+            rollback,
+        } => {
+            if rollback {
+                commands::use_version::rollback(install_dir)?;
+            } else {
+                commands::update::run(force, version, install_dir, allow_downgrade).await?;
+            }
         }
+        Commands::Use {
+            version,
+            install_dir,
+        } => match version {
+            Some(version) => commands::use_version::run(&version, install_dir)?,
+            None => commands::use_version::list(install_dir)?,
+        },
         Commands::Version => {
             commands::version::check()?;
         }
+        Commands::SelfUpdate { force, yes } => {
+            commands::self_update::run(force, yes).await?;
+        }
+        Commands::Uninstall {
+            repo_only: _,
+            binary,
+            all,
+            dry_run,
+            yes,
+        } => {
+            let scope = if all {
+                commands::uninstall::Scope::All
+            } else if binary {
+                commands::uninstall::Scope::Binary
+            } else {
+                commands::uninstall::Scope::Repo
+            };
+            commands::uninstall::run(scope, dry_run, yes)?;
+        }
+        Commands::Cache { action } => match action {
+            CacheAction::List => commands::cache::list()?,
+            CacheAction::Clean => commands::cache::clean()?,
+        },
+        Commands::Config { action } => match action {
+            ConfigAction::Validate { path } => commands::config::validate(&path)?,
+            ConfigAction::Link { source } => commands::config::link(&source)?,
+            ConfigAction::Sync { locked } => commands::config::sync(locked)?,
+            ConfigAction::Templates => commands::config::templates()?,
+        },
+        Commands::Allow { config, action } => match action {
+            AllowAction::Path { regex } => commands::allow::add(&config, config::AllowKind::Path, &regex)?,
+            AllowAction::Regex { pattern } => commands::allow::add(&config, config::AllowKind::Regex, &pattern)?,
+            AllowAction::Commit { sha } => commands::allow::add(&config, config::AllowKind::Commit, &sha)?,
+            AllowAction::List => commands::allow::list(&config)?,
+            AllowAction::Remove { entry } => commands::allow::remove(&config, &entry)?,
+        },
+        Commands::Ignore {
+            fingerprints,
+            from_report,
+            rule,
+            reason,
+            action,
+        } => match action {
+            Some(IgnoreAction::Prune { dry_run }) => commands::ignore::prune(dry_run)?,
+            None => commands::ignore::add(fingerprints, from_report, rule, reason)?,
+        },
+        Commands::Rules { action } => match action {
+            RulesAction::Test { fixtures, config } => commands::rules::test(&fixtures, &config)?,
+            RulesAction::Add {
+                id,
+                description,
+                regex,
+                keywords,
+                entropy,
+                secret_group,
+                tags,
+                samples,
+                config,
+            } => {
+                let rule = config::NewRule {
+                    id: id.unwrap_or_default(),
+                    description: description.unwrap_or_default(),
+                    regex: regex.unwrap_or_default(),
+                    keywords,
+                    entropy,
+                    secret_group,
+                    tags,
+                };
+                commands::rules::add(&config, rule, samples)?;
+            }
+        },
+        Commands::RefreshUpdateCheck => {
+            update_check::refresh()?;
+        }
+    }
+
+    if notify {
+        update_check::notify();
     }
 
     Ok(())
@@ -0,0 +1,539 @@
+use anyhow::{Context, Result};
+use chrono::{Local, TimeZone};
+use indicatif::{ProgressBar, ProgressStyle};
+use reqwest::blocking::{Client, Response};
+use reqwest::header::{HeaderMap, AUTHORIZATION, RANGE};
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::fs::{self, File, OpenOptions};
+use std::io::{Read, Write};
+use std::path::Path;
+use std::thread;
+use std::time::Duration;
+
+use crate::settings::Settings;
+
+/// Default API base for gitleaks releases
+pub const DEFAULT_API_URL: &str = "https://api.github.com/repos/gitleaks/gitleaks";
+
+/// Environment variable overriding the API base for gitleaks releases
+pub const API_URL_ENV: &str = "GITLEAKS_GUARD_RELEASES_URL";
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct GithubAsset {
+    pub name: String,
+    pub browser_download_url: String,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct GithubRelease {
+    pub tag_name: String,
+    #[serde(default)]
+    pub assets: Vec<GithubAsset>,
+    /// Release notes (Markdown)
+    #[serde(default)]
+    pub body: Option<String>,
+    #[serde(default)]
+    pub html_url: Option<String>,
+}
+
+impl GithubRelease {
+    /// Find an asset by exact name
+    pub fn asset(&self, name: &str) -> Option<&GithubAsset> {
+        self.assets.iter().find(|a| a.name == name)
+    }
+
+    /// Find the checksums file published alongside the release archives
+    pub fn checksums_asset(&self) -> Option<&GithubAsset> {
+        self.assets.iter().find(|a| a.name.ends_with("checksums.txt"))
+    }
+}
+
+/// API base for gitleaks-guard's own releases
+pub const SELF_API_URL: &str = "https://api.github.com/repos/ruslanlap/pre-commit-auto-script";
+
+/// Environment variable overriding the API base for gitleaks-guard's own releases
+pub const SELF_API_URL_ENV: &str = "GITLEAKS_GUARD_SELF_RELEASES_URL";
+
+/// Attempts made before a download is given up on
+const DOWNLOAD_ATTEMPTS: u32 = 5;
+
+/// Delay before the first download retry; doubled after every failed attempt
+const INITIAL_BACKOFF: Duration = Duration::from_millis(500);
+
+/// Environment variables checked, in order, for a GitHub token
+const TOKEN_ENV_VARS: [&str; 2] = ["GITHUB_TOKEN", "GH_TOKEN"];
+
+/// API base gitleaks releases are discovered from
+///
+/// `GITLEAKS_GUARD_RELEASES_URL` wins over `releases.api_url` in the settings file, which wins
+/// over the public GitHub API. The URL must serve GitHub's `/releases/latest` and
+/// `/releases/tags/<tag>` endpoints.
+pub fn gitleaks_api_url() -> Result<String> {
+    let url = match env::var(API_URL_ENV) {
+        Ok(url) if !url.trim().is_empty() => url,
+        _ => Settings::load()?
+            .releases
+            .api_url
+            .unwrap_or_else(|| DEFAULT_API_URL.to_string()),
+    };
+    Ok(url.trim().trim_end_matches('/').to_string())
+}
+
+/// API base gitleaks-guard's own releases are discovered from by `self-update`
+pub fn self_api_url() -> String {
+    match env::var(SELF_API_URL_ENV) {
+        Ok(url) if !url.trim().is_empty() => url.trim().trim_end_matches('/').to_string(),
+        _ => SELF_API_URL.to_string(),
+    }
+}
+
+/// Run blocking HTTP work from inside the async command handlers
+///
+/// The blocking reqwest client owns a runtime of its own, which tokio refuses to drop on an
+/// async worker thread.
+pub fn blocking<T>(f: impl FnOnce() -> T) -> T {
+    tokio::task::block_in_place(f)
+}
+
+/// Build the HTTP client used for release discovery and downloads
+pub fn client() -> Result<Client> {
+    Client::builder()
+        .user_agent("gitleaks-guard")
+        .build()
+        .context("Failed to create HTTP client")
+}
+
+/// Fetch release metadata for `tag`, or the latest release when `tag` is `None`
+pub fn fetch_release(client: &Client, api_url: &str, tag: Option<&str>) -> Result<GithubRelease> {
+    let url = match tag {
+        Some(tag) => format!("{}/releases/tags/{}", api_url, tag),
+        None => format!("{}/releases/latest", api_url),
+    };
+
+    let response = api_get(client, &url)
+        .with_context(|| format!("Failed to fetch releases from {}", api_url))?;
+
+    if response.status() == StatusCode::NOT_FOUND {
+        match tag {
+            Some(tag) => anyhow::bail!("Release {} does not exist at {}", tag, api_url),
+            None => anyhow::bail!("No release found at {}", api_url),
+        }
+    }
+
+    check_api_response(response)?
+        .json()
+        .context("Failed to parse release data")
+}
+
+/// Upper bound on release list pages followed, in case a mirror keeps linking to more
+const MAX_RELEASE_PAGES: usize = 10;
+
+/// Fetch the releases, newest first, following the `Link: rel="next"` pagination
+///
+/// At most [`MAX_RELEASE_PAGES`] pages are read, so the oldest releases may be missing.
+pub fn fetch_releases(client: &Client, api_url: &str) -> Result<Vec<GithubRelease>> {
+    let mut releases = Vec::new();
+    let mut next = Some(format!("{}/releases?per_page=100", api_url));
+    for _ in 0..MAX_RELEASE_PAGES {
+        let Some(url) = next.take() else { break };
+        let response = api_get(client, &url)
+            .with_context(|| format!("Failed to fetch releases from {}", api_url))?;
+        let response = check_api_response(response)?;
+        next = next_page(response.headers());
+
+        let page: Vec<GithubRelease> = response.json().context("Failed to parse release data")?;
+        releases.extend(page);
+    }
+    Ok(releases)
+}
+
+/// The `rel="next"` target of a GitHub `Link` header
+fn next_page(headers: &HeaderMap) -> Option<String> {
+    let link = headers.get("link")?.to_str().ok()?;
+    link.split(',').find_map(|entry| {
+        let (target, params) = entry.split_once(';')?;
+        params
+            .split(';')
+            .any(|param| param.trim() == "rel=\"next\"")
+            .then(|| target.trim().trim_start_matches('<').trim_end_matches('>').to_string())
+    })
+}
+
+/// Send a GET request to the releases API, authenticated when the host may see the token
+fn api_get(client: &Client, url: &str) -> reqwest::Result<Response> {
+    let mut request = client.get(url).header("Accept", "application/vnd.github+json");
+    if let Some(token) = github_token_for(url) {
+        request = request.header(AUTHORIZATION, format!("Bearer {}", token));
+    }
+    request.send()
+}
+
+/// GitHub token to send to `url`, if any
+///
+/// Mirrors and other third-party hosts never see the token; see [`sends_token_to`].
+fn github_token_for(url: &str) -> Option<String> {
+    let github_hosts = Settings::load()
+        .map(|settings| settings.releases.github_hosts)
+        .unwrap_or_default();
+    github_token().filter(|_| sends_token_to(url, &github_hosts))
+}
+
+/// Whether a token may be sent to `url`: only over https, and only to `api.github.com` or a
+/// GitHub Enterprise host listed in `releases.github_hosts`
+fn sends_token_to(url: &str, github_hosts: &[String]) -> bool {
+    let Ok(url) = reqwest::Url::parse(url) else {
+        return false;
+    };
+    if url.scheme() != "https" {
+        return false;
+    }
+    url.host_str().is_some_and(|host| {
+        host.eq_ignore_ascii_case("api.github.com")
+            || github_hosts.iter().any(|allowed| allowed.trim().eq_ignore_ascii_case(host))
+    })
+}
+
+/// GitHub token from `GITHUB_TOKEN` or `GH_TOKEN`, used to lift the anonymous rate limit
+fn github_token() -> Option<String> {
+    TOKEN_ENV_VARS
+        .iter()
+        .filter_map(|var| env::var(var).ok())
+        .map(|token| token.trim().to_string())
+        .find(|token| !token.is_empty())
+}
+
+/// Turn error responses into readable errors instead of letting JSON parsing fail on them
+fn check_api_response(response: Response) -> Result<Response> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+
+    let has_token = github_token_for(response.url().as_str()).is_some();
+    if let Some(message) = rate_limit_message(status, response.headers(), has_token) {
+        anyhow::bail!(message);
+    }
+
+    let body = response.text().unwrap_or_default();
+    let detail = serde_json::from_str::<serde_json::Value>(&body)
+        .ok()
+        .and_then(|json| json["message"].as_str().map(str::to_string))
+        .unwrap_or(body);
+    anyhow::bail!("GitHub API request failed ({}): {}", status, detail.trim())
+}
+
+/// Describe a rate-limited response, or `None` if the response is not a rate limit
+fn rate_limit_message(status: StatusCode, headers: &HeaderMap, has_token: bool) -> Option<String> {
+    let header = |name: &str| headers.get(name).and_then(|v| v.to_str().ok());
+
+    let exhausted = header("x-ratelimit-remaining") == Some("0");
+    let retry_after = header("retry-after").and_then(|v| v.parse::<u64>().ok());
+    let limited = match status {
+        StatusCode::TOO_MANY_REQUESTS => true,
+        StatusCode::FORBIDDEN => exhausted || retry_after.is_some(),
+        _ => false,
+    };
+    if !limited {
+        return None;
+    }
+
+    let mut message = format!("GitHub API rate limit exceeded ({}).", status);
+    match (retry_after, header("x-ratelimit-reset").and_then(|v| v.parse::<i64>().ok())) {
+        (Some(seconds), _) => message.push_str(&format!(" Retry in {} seconds.", seconds)),
+        (None, Some(epoch)) => {
+            if let Some(at) = Local.timestamp_opt(epoch, 0).single() {
+                message.push_str(&format!(" The limit resets at {}.", at.format("%Y-%m-%d %H:%M:%S")));
+            }
+        }
+        (None, None) => {}
+    }
+    message.push(' ');
+    message.push_str(if has_token {
+        "The token's own rate limit is exhausted; wait for the reset or use a different token."
+    } else {
+        "Set GITHUB_TOKEN or GH_TOKEN to authenticate and raise the limit from 60 to 5000 requests/hour."
+    });
+
+    Some(message)
+}
+
+/// Download a small text asset such as a checksums file
+pub fn fetch_text(client: &Client, url: &str) -> Result<String> {
+    client
+        .get(url)
+        .send()
+        .and_then(|r| r.error_for_status())
+        .and_then(|r| r.text())
+        .with_context(|| format!("Failed to download {}", url))
+}
+
+/// Progress bar used for release downloads
+pub fn progress_bar() -> ProgressBar {
+    let pb = ProgressBar::new(0);
+    pb.set_style(
+        ProgressStyle::default_bar()
+            .template("{spinner:.green} [{bar:40.cyan/blue}] {bytes}/{total_bytes} ({eta})")
+            .expect("Failed to create progress bar")
+            .progress_chars("#>-"),
+    );
+    pb
+}
+
+/// A failed download attempt, and whether retrying it could help
+struct AttemptError {
+    error: anyhow::Error,
+    transient: bool,
+}
+
+impl AttemptError {
+    fn transient(error: impl Into<anyhow::Error>) -> Self {
+        AttemptError {
+            error: error.into(),
+            transient: true,
+        }
+    }
+
+    fn fatal(error: impl Into<anyhow::Error>) -> Self {
+        AttemptError {
+            error: error.into(),
+            transient: false,
+        }
+    }
+}
+
+/// Stream `url` into `dest`, updating `progress` as bytes arrive
+///
+/// Transient failures are retried with exponential backoff, and a partially written `dest` is
+/// resumed with an HTTP Range request instead of starting over.
+pub fn download_file(client: &Client, url: &str, dest: &Path, progress: &ProgressBar) -> Result<()> {
+    let mut backoff = INITIAL_BACKOFF;
+    for attempt in 1..=DOWNLOAD_ATTEMPTS {
+        match download_attempt(client, url, dest, progress) {
+            Ok(()) => {
+                progress.finish_with_message("Download complete");
+                return Ok(());
+            }
+            Err(e) if e.transient && attempt < DOWNLOAD_ATTEMPTS => {
+                progress.println(format!(
+                    "⚠ Download interrupted ({:#}); retrying in {:.1}s ({}/{})",
+                    e.error,
+                    backoff.as_secs_f32(),
+                    attempt,
+                    DOWNLOAD_ATTEMPTS - 1
+                ));
+                thread::sleep(backoff);
+                backoff *= 2;
+            }
+            Err(e) => {
+                progress.abandon();
+                return Err(e.error);
+            }
+        }
+    }
+    unreachable!("the last attempt always returns")
+}
+
+fn download_attempt(
+    client: &Client,
+    url: &str,
+    dest: &Path,
+    progress: &ProgressBar,
+) -> std::result::Result<(), AttemptError> {
+    let existing = fs::metadata(dest).map(|m| m.len()).unwrap_or(0);
+
+    let mut request = client.get(url);
+    if existing > 0 {
+        request = request.header(RANGE, format!("bytes={}-", existing));
+    }
+    let mut response = request.send().map_err(AttemptError::transient)?;
+
+    let status = response.status();
+    if status == StatusCode::RANGE_NOT_SATISFIABLE {
+        // The partial file is unusable (e.g. the asset changed); start from scratch
+        fs::remove_file(dest).map_err(AttemptError::fatal)?;
+        return Err(AttemptError::transient(anyhow::anyhow!("server rejected resume request")));
+    }
+    if status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS {
+        return Err(AttemptError::transient(anyhow::anyhow!("server returned {}", status)));
+    }
+    if !status.is_success() {
+        return Err(AttemptError::fatal(anyhow::anyhow!("server returned {}", status)));
+    }
+
+    // Servers that ignore Range send the whole file again
+    let resumed = status == StatusCode::PARTIAL_CONTENT;
+    let start = if resumed { existing } else { 0 };
+    let mut file = if resumed {
+        OpenOptions::new().append(true).open(dest)
+    } else {
+        File::create(dest)
+    }
+    .with_context(|| format!("Failed to open {}", dest.display()))
+    .map_err(AttemptError::fatal)?;
+
+    let total = response.content_length().map(|len| len + start);
+    progress.set_length(total.unwrap_or(0));
+    progress.set_position(start);
+
+    let mut written = start;
+    let mut buffer = [0u8; 64 * 1024];
+    loop {
+        let read = response.read(&mut buffer).map_err(AttemptError::transient)?;
+        if read == 0 {
+            break;
+        }
+        file.write_all(&buffer[..read])
+            .context("Failed to write download")
+            .map_err(AttemptError::fatal)?;
+        written += read as u64;
+        progress.set_position(written);
+    }
+
+    match total {
+        Some(total) if written < total => Err(AttemptError::transient(anyhow::anyhow!(
+            "connection closed after {} of {} bytes",
+            written,
+            total
+        ))),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+    use std::io::{BufRead, BufReader};
+    use std::net::TcpListener;
+
+    /// Serve `body` once truncated halfway, then honor the Range request that follows
+    fn serve_flaky(body: &'static [u8]) -> (String, thread::JoinHandle<Vec<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/archive.tar.gz", listener.local_addr().unwrap());
+
+        let handle = thread::spawn(move || {
+            let mut ranges = Vec::new();
+            for attempt in 0..2 {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut range = String::new();
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    if line == "\r\n" || line.is_empty() {
+                        break;
+                    }
+                    if line.to_lowercase().starts_with("range:") {
+                        range = line[6..].trim().to_string();
+                    }
+                }
+                ranges.push(range);
+
+                if attempt == 0 {
+                    let head = format!("HTTP/1.1 200 OK\r\nContent-Length: {}\r\n\r\n", body.len());
+                    stream.write_all(head.as_bytes()).unwrap();
+                    stream.write_all(&body[..body.len() / 2]).unwrap();
+                } else {
+                    let rest = &body[body.len() / 2..];
+                    let head = format!(
+                        "HTTP/1.1 206 Partial Content\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                        rest.len()
+                    );
+                    stream.write_all(head.as_bytes()).unwrap();
+                    stream.write_all(rest).unwrap();
+                }
+            }
+            ranges
+        });
+
+        (url, handle)
+    }
+
+    #[test]
+    fn resumes_interrupted_download() {
+        let body: &'static [u8] = b"0123456789abcdefghijklmnopqrstuvwxyz";
+        let (url, server) = serve_flaky(body);
+        let dir = tempfile::tempdir().unwrap();
+        let dest = dir.path().join("archive.tar.gz");
+
+        let progress = ProgressBar::hidden();
+        download_file(&client().unwrap(), &url, &dest, &progress).unwrap();
+
+        assert_eq!(fs::read(&dest).unwrap(), body);
+        assert_eq!(progress.position(), body.len() as u64);
+        let ranges = server.join().unwrap();
+        assert_eq!(ranges, vec![String::new(), format!("bytes={}-", body.len() / 2)]);
+    }
+
+    fn headers(pairs: &[(&'static str, &str)]) -> HeaderMap {
+        let mut map = HeaderMap::new();
+        for (name, value) in pairs {
+            map.insert(*name, HeaderValue::from_str(value).unwrap());
+        }
+        map
+    }
+
+    #[test]
+    fn reports_exhausted_anonymous_limit() {
+        let h = headers(&[("x-ratelimit-remaining", "0"), ("x-ratelimit-reset", "1700000000")]);
+        let message = rate_limit_message(StatusCode::FORBIDDEN, &h, false).unwrap();
+        assert!(message.contains("rate limit exceeded"));
+        assert!(message.contains("The limit resets at"));
+        assert!(message.contains("GITHUB_TOKEN"));
+    }
+
+    #[test]
+    fn reports_retry_after_for_token() {
+        let h = headers(&[("retry-after", "30")]);
+        let message = rate_limit_message(StatusCode::TOO_MANY_REQUESTS, &h, true).unwrap();
+        assert!(message.contains("Retry in 30 seconds."));
+        assert!(message.contains("token's own rate limit"));
+    }
+
+    #[test]
+    fn ignores_other_forbidden_responses() {
+        let h = headers(&[("x-ratelimit-remaining", "42")]);
+        assert!(rate_limit_message(StatusCode::FORBIDDEN, &h, false).is_none());
+        assert!(rate_limit_message(StatusCode::INTERNAL_SERVER_ERROR, &h, false).is_none());
+    }
+
+    #[test]
+    fn follows_next_link_only() {
+        let h = headers(&[(
+            "link",
+            "<https://api.github.com/repositories/1/releases?per_page=100&page=3>; rel=\"next\", \
+             <https://api.github.com/repositories/1/releases?per_page=100&page=5>; rel=\"last\"",
+        )]);
+        assert_eq!(
+            next_page(&h).as_deref(),
+            Some("https://api.github.com/repositories/1/releases?per_page=100&page=3")
+        );
+
+        let last_page = headers(&[(
+            "link",
+            "<https://api.github.com/repositories/1/releases?per_page=100&page=1>; rel=\"first\"",
+        )]);
+        assert_eq!(next_page(&last_page), None);
+        assert_eq!(next_page(&HeaderMap::new()), None);
+    }
+
+    #[test]
+    fn sends_token_only_to_github_over_https() {
+        assert!(sends_token_to("https://api.github.com/repos/gitleaks/gitleaks/releases/latest", &[]));
+        assert!(sends_token_to("https://API.GitHub.com/repos/gitleaks/gitleaks", &[]));
+        assert!(!sends_token_to("http://api.github.com/repos/gitleaks/gitleaks", &[]));
+        assert!(!sends_token_to("https://artifactory.example.com/api/vcs/github/repos/gitleaks/gitleaks", &[]));
+        assert!(!sends_token_to("https://api.github.com.example.com/repos/gitleaks/gitleaks", &[]));
+        assert!(!sends_token_to("not a url", &[]));
+    }
+
+    #[test]
+    fn sends_token_to_listed_enterprise_hosts_over_https() {
+        let hosts = vec!["github.example.com".to_string()];
+        assert!(sends_token_to("https://github.example.com/api/v3/repos/gitleaks/gitleaks", &hosts));
+        assert!(!sends_token_to("http://github.example.com/api/v3/repos/gitleaks/gitleaks", &hosts));
+        assert!(!sends_token_to("https://mirror.example.com/repos/gitleaks/gitleaks", &hosts));
+    }
+}
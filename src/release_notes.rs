@@ -0,0 +1,162 @@
+use semver::Version;
+
+use crate::release::GithubRelease;
+
+/// Words that mark a release note as relevant to rule authors and config maintainers
+const NOTABLE_KEYWORDS: [&str; 7] = [
+    "rule",
+    "config",
+    "allowlist",
+    "toml",
+    "syntax",
+    "breaking",
+    "deprecat",
+];
+
+/// One condensed release note entry
+#[derive(Debug, PartialEq, Eq)]
+pub struct NoteLine {
+    pub text: String,
+    /// Mentions rules, config syntax or breaking changes
+    pub notable: bool,
+}
+
+/// Releases newer than `current` up to and including `target`, oldest first
+pub fn releases_between<'a>(
+    releases: &'a [GithubRelease],
+    current: &Version,
+    target: &Version,
+) -> Vec<(Version, &'a GithubRelease)> {
+    let mut between: Vec<_> = releases
+        .iter()
+        .filter_map(|release| {
+            let version = Version::parse(release.tag_name.trim_start_matches('v')).ok()?;
+            (version > *current && version <= *target).then_some((version, release))
+        })
+        .collect();
+    between.sort_by(|a, b| a.0.cmp(&b.0));
+    between
+}
+
+/// Reduce a Markdown release body to its list entries, without commit hashes, links and credits
+///
+/// Bodies without any list fall back to their plain text lines.
+pub fn condense(body: &str) -> Vec<NoteLine> {
+    let lines: Vec<&str> = body.lines().map(str::trim).collect();
+    let items: Vec<&str> = lines
+        .iter()
+        .filter_map(|line| {
+            line.strip_prefix("- ")
+                .or_else(|| line.strip_prefix("* "))
+                .or_else(|| line.strip_prefix("+ "))
+        })
+        .collect();
+
+    let entries = if items.is_empty() {
+        lines
+            .into_iter()
+            .filter(|line| !line.is_empty() && !line.starts_with('#') && !line.starts_with("**Full Changelog"))
+            .collect()
+    } else {
+        items
+    };
+
+    entries
+        .into_iter()
+        .map(clean_entry)
+        .filter(|text| !text.is_empty())
+        .map(|text| NoteLine {
+            notable: is_notable(&text),
+            text,
+        })
+        .collect()
+}
+
+fn clean_entry(entry: &str) -> String {
+    let mut text = entry.trim();
+
+    // goreleaser changelogs prefix each entry with the commit hash
+    if let Some((first, rest)) = text.split_once(' ') {
+        if first.len() >= 7 && first.chars().all(|c| c.is_ascii_hexdigit()) {
+            text = rest.trim_start().trim_start_matches(':').trim_start();
+        }
+    }
+
+    // GitHub generated notes end with "by @user in <pull request URL>"
+    if let Some(idx) = text.rfind(" by @") {
+        if text[idx..].contains(" in http") {
+            text = &text[..idx];
+        }
+    }
+
+    strip_links(text).trim().to_string()
+}
+
+/// Replace Markdown links `[text](url)` with their text
+fn strip_links(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(open) = rest.find('[') {
+        let Some(close) = rest[open..].find("](").map(|i| open + i) else {
+            break;
+        };
+        let Some(end) = rest[close..].find(')').map(|i| close + i) else {
+            break;
+        };
+        out.push_str(&rest[..open]);
+        out.push_str(&rest[open + 1..close]);
+        rest = &rest[end + 1..];
+    }
+    out.push_str(rest);
+    out
+}
+
+fn is_notable(text: &str) -> bool {
+    let lower = text.to_lowercase();
+    NOTABLE_KEYWORDS.iter().any(|keyword| lower.contains(keyword))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn release(tag: &str) -> GithubRelease {
+        GithubRelease {
+            tag_name: tag.to_string(),
+            assets: Vec::new(),
+            body: None,
+            html_url: None,
+        }
+    }
+
+    #[test]
+    fn selects_releases_in_range() {
+        let releases = vec![release("v8.19.0"), release("v8.18.1"), release("v8.18.0"), release("v8.17.0")];
+        let between = releases_between(
+            &releases,
+            &Version::new(8, 17, 0),
+            &Version::new(8, 18, 1),
+        );
+        let tags: Vec<_> = between.iter().map(|(_, r)| r.tag_name.as_str()).collect();
+        assert_eq!(tags, vec!["v8.18.0", "v8.18.1"]);
+    }
+
+    #[test]
+    fn condenses_release_body() {
+        let body = "## Changelog\n\
+            * 1a2b3c4d add [Stripe](https://stripe.com) rule\n\
+            * 5e6f7a8b fix typo in README\n\
+            - Drop support for `allowlist.paths` by @someone in https://github.com/gitleaks/gitleaks/pull/1\n\
+            \n\
+            **Full Changelog**: https://github.com/gitleaks/gitleaks/compare/v8.17.0...v8.18.0\n";
+
+        assert_eq!(
+            condense(body),
+            vec![
+                NoteLine { text: "add Stripe rule".to_string(), notable: true },
+                NoteLine { text: "fix typo in README".to_string(), notable: false },
+                NoteLine { text: "Drop support for `allowlist.paths`".to_string(), notable: true },
+            ]
+        );
+    }
+}
@@ -0,0 +1,37 @@
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::fs;
+use std::path::Path;
+
+/// A finding from a gitleaks JSON report
+#[derive(Debug, Clone, Deserialize)]
+pub struct Finding {
+    #[serde(rename = "RuleID")]
+    pub rule_id: String,
+    #[serde(rename = "File")]
+    pub file: String,
+    #[serde(rename = "StartLine", default)]
+    pub start_line: u64,
+    /// Identifier gitleaks matches `.gitleaksignore` entries against
+    #[serde(rename = "Fingerprint", default)]
+    pub fingerprint: String,
+}
+
+impl Finding {
+    /// Fingerprint of the finding without its commit, as reported by `--no-git` scans
+    pub fn location_fingerprint(&self) -> String {
+        format!("{}:{}:{}", self.file, self.rule_id, self.start_line)
+    }
+}
+
+/// Parse the findings of a gitleaks JSON report
+pub fn parse(json: &str) -> Result<Vec<Finding>> {
+    serde_json::from_str(json).context("Failed to parse gitleaks report")
+}
+
+/// Load the findings of a gitleaks JSON report file
+pub fn load(path: &Path) -> Result<Vec<Finding>> {
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    parse(&content).with_context(|| format!("{} is not a gitleaks JSON report", path.display()))
+}
@@ -0,0 +1,55 @@
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::utils;
+
+/// Environment variable overriding the location of the settings file
+pub const SETTINGS_PATH_ENV: &str = "GITLEAKS_GUARD_CONFIG";
+
+/// User-level gitleaks-guard settings, read from `~/.config/gitleaks-guard/config.toml`
+#[derive(Debug, Default, Deserialize)]
+pub struct Settings {
+    #[serde(default)]
+    pub releases: ReleaseSettings,
+    #[serde(default)]
+    pub updates: UpdateSettings,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct ReleaseSettings {
+    /// GitHub-compatible API base for gitleaks releases (e.g. a GHE or Artifactory mirror)
+    pub api_url: Option<String>,
+    /// GitHub Enterprise API hosts that may receive `GITHUB_TOKEN`, besides `api.github.com`
+    #[serde(default)]
+    pub github_hosts: Vec<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct UpdateSettings {
+    /// Hours between background checks for newer releases; `0` turns the check off
+    pub check_interval_hours: Option<u64>,
+}
+
+impl Settings {
+    /// Path of the settings file, honoring `GITLEAKS_GUARD_CONFIG`
+    pub fn path() -> Option<PathBuf> {
+        match env::var_os(SETTINGS_PATH_ENV) {
+            Some(path) => Some(PathBuf::from(path)),
+            None => utils::config_dir().map(|dir| dir.join("config.toml")),
+        }
+    }
+
+    /// Load settings, falling back to defaults when no settings file exists
+    pub fn load() -> Result<Self> {
+        let path = match Self::path() {
+            Some(path) if path.exists() => path,
+            _ => return Ok(Settings::default()),
+        };
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        toml::from_str(&content).with_context(|| format!("Failed to parse {}", path.display()))
+    }
+}
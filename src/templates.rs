@@ -0,0 +1,194 @@
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::{gitleaks, utils};
+
+/// Name of the default template, the bundled config matching the installed gitleaks
+pub const STANDARD: &str = "standard";
+
+/// Templates shipped with gitleaks-guard, in menu order after `standard`
+const BUILT_IN: [(&str, &str, &str); 7] = [
+    (
+        "strict",
+        "More aggressive detection with a minimal allowlist",
+        include_str!("../config/templates/strict.toml"),
+    ),
+    (
+        "minimal",
+        "Basic detection only",
+        include_str!("../config/templates/minimal.toml"),
+    ),
+    (
+        "node",
+        "Node.js: skips node_modules, lockfiles, build output and fixtures",
+        include_str!("../config/templates/node.toml"),
+    ),
+    (
+        "python",
+        "Python: skips virtualenvs, caches, lockfiles and fixtures",
+        include_str!("../config/templates/python.toml"),
+    ),
+    (
+        "terraform",
+        "Terraform: skips provider caches and variable references, scans state",
+        include_str!("../config/templates/terraform.toml"),
+    ),
+    (
+        "kubernetes",
+        "Kubernetes/Helm: skips vendored charts and template placeholders",
+        include_str!("../config/templates/kubernetes.toml"),
+    ),
+    (
+        "mobile",
+        "iOS/Android/Flutter/React Native: skips Pods, Gradle and assets",
+        include_str!("../config/templates/mobile.toml"),
+    ),
+];
+
+/// A config template `init` and `install` can create `.gitleaks.toml` from
+#[derive(Debug, Clone)]
+pub struct Template {
+    pub name: String,
+    pub description: String,
+    pub source: TemplateSource,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TemplateSource {
+    /// The bundled default config, picked for the installed gitleaks version
+    Standard,
+    BuiltIn(&'static str),
+    /// A team template from the templates directory
+    File(PathBuf),
+}
+
+impl Template {
+    /// Config contents; `installed` is the `gitleaks version` output, used by the standard template
+    pub fn content(&self, installed: Option<&str>) -> Result<String> {
+        match &self.source {
+            TemplateSource::Standard => Ok(gitleaks::bundled_config(installed).to_string()),
+            TemplateSource::BuiltIn(content) => Ok(content.to_string()),
+            TemplateSource::File(path) => {
+                fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))
+            }
+        }
+    }
+
+    pub fn is_custom(&self) -> bool {
+        matches!(self.source, TemplateSource::File(_))
+    }
+}
+
+/// Directory teams drop their own `<name>.toml` templates into
+/// (`~/.config/gitleaks-guard/templates`)
+pub fn templates_dir() -> Option<PathBuf> {
+    utils::config_dir().map(|dir| dir.join("templates"))
+}
+
+/// All templates: standard, the built-ins, then custom ones
+///
+/// A custom template named like a built-in one replaces it.
+pub fn all() -> Result<Vec<Template>> {
+    list_in(templates_dir().as_deref())
+}
+
+/// Look up a template by name
+pub fn find(name: &str) -> Result<Template> {
+    let templates = all()?;
+    match templates.iter().find(|t| t.name.eq_ignore_ascii_case(name)) {
+        Some(template) => Ok(template.clone()),
+        None => {
+            let names: Vec<&str> = templates.iter().map(|t| t.name.as_str()).collect();
+            anyhow::bail!("Unknown template '{}'. Available: {}", name, names.join(", "))
+        }
+    }
+}
+
+fn list_in(dir: Option<&Path>) -> Result<Vec<Template>> {
+    let mut templates = vec![Template {
+        name: STANDARD.to_string(),
+        description: "Detects common secrets with the rules built into gitleaks (recommended)".to_string(),
+        source: TemplateSource::Standard,
+    }];
+    templates.extend(BUILT_IN.iter().map(|(name, description, content)| Template {
+        name: name.to_string(),
+        description: description.to_string(),
+        source: TemplateSource::BuiltIn(content),
+    }));
+
+    let Some(dir) = dir.filter(|dir| dir.is_dir()) else {
+        return Ok(templates);
+    };
+    let mut paths: Vec<PathBuf> = fs::read_dir(dir)
+        .with_context(|| format!("Failed to read {}", dir.display()))?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "toml"))
+        .collect();
+    paths.sort();
+
+    for path in paths {
+        let Some(name) = path.file_stem().map(|s| s.to_string_lossy().to_string()) else {
+            continue;
+        };
+        let template = Template {
+            description: custom_description(&path),
+            name,
+            source: TemplateSource::File(path),
+        };
+        match templates.iter_mut().find(|t| t.name == template.name) {
+            Some(existing) => *existing = template,
+            None => templates.push(template),
+        }
+    }
+    Ok(templates)
+}
+
+/// Describe a custom template by its `description` or `title`
+fn custom_description(path: &Path) -> String {
+    let table = fs::read_to_string(path)
+        .ok()
+        .and_then(|content| toml::from_str::<toml::Table>(&content).ok());
+    let Some(table) = table else {
+        return "Custom template (not valid TOML)".to_string();
+    };
+    ["description", "title"]
+        .iter()
+        .find_map(|key| table.get(*key).and_then(|v| v.as_str()))
+        .map(str::to_string)
+        .unwrap_or_else(|| "No description".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lint;
+
+    #[test]
+    fn built_in_templates_are_valid() {
+        for template in list_in(None).unwrap() {
+            let content = template.content(None).unwrap();
+            assert!(lint::validate(&content).is_empty(), "template '{}' has problems", template.name);
+        }
+    }
+
+    #[test]
+    fn custom_templates_extend_and_override_built_ins() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("acme.toml"), "title = \"Acme services\"\n").unwrap();
+        fs::write(dir.path().join("node.toml"), "description = \"Our Node setup\"\n").unwrap();
+        fs::write(dir.path().join("notes.txt"), "not a template").unwrap();
+
+        let templates = list_in(Some(dir.path())).unwrap();
+        assert_eq!(templates.len(), BUILT_IN.len() + 2);
+        assert_eq!(templates[0].source, TemplateSource::Standard);
+
+        let node = templates.iter().find(|t| t.name == "node").unwrap();
+        assert!(node.is_custom());
+        assert_eq!(node.description, "Our Node setup");
+
+        let acme = templates.last().unwrap();
+        assert_eq!((acme.name.as_str(), acme.description.as_str()), ("acme", "Acme services"));
+        assert_eq!(acme.content(None).unwrap(), "title = \"Acme services\"\n");
+    }
+}
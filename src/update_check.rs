@@ -0,0 +1,194 @@
+use anyhow::{Context, Result};
+use chrono::Utc;
+use colored::*;
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::fs;
+use std::io::IsTerminal;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+
+use crate::settings::Settings;
+use crate::{gitleaks, release, utils};
+
+/// Hours between background update checks unless `updates.check_interval_hours` says otherwise
+const DEFAULT_INTERVAL_HOURS: u64 = 24;
+
+/// Environment variable that turns the background update check off
+pub const DISABLE_ENV: &str = "GITLEAKS_GUARD_NO_UPDATE_CHECK";
+
+/// Result of the last background check, cached so commands never wait on the network
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct UpdateState {
+    /// Unix timestamp of the last check
+    pub checked_at: i64,
+    /// Latest gitleaks release, without the leading `v`
+    pub latest_gitleaks: Option<String>,
+    /// Latest gitleaks-guard release, without the leading `v`
+    pub latest_guard: Option<String>,
+}
+
+impl UpdateState {
+    fn path() -> Option<PathBuf> {
+        utils::cache_dir().map(|dir| dir.join("update-check.json"))
+    }
+
+    /// Load the cached state, treating a missing or unreadable file as never checked
+    pub fn load() -> Self {
+        Self::path()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let path = Self::path().context("Could not determine the cache directory")?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)
+                .with_context(|| format!("Failed to create {}", dir.display()))?;
+        }
+        fs::write(&path, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    /// Whether the last check is older than `interval_hours`
+    pub fn is_stale(&self, now: i64, interval_hours: u64) -> bool {
+        now - self.checked_at >= (interval_hours * 3600) as i64
+    }
+
+    /// One-line notices for every release newer than what is installed
+    pub fn notices(&self, installed_gitleaks: Option<&str>, installed_guard: &str) -> Vec<String> {
+        let mut notices = Vec::new();
+        let installed_gitleaks = installed_gitleaks.and_then(gitleaks::parse_version);
+        if let (Some(latest), Some(installed)) = (&self.latest_gitleaks, installed_gitleaks) {
+            if is_newer(latest, &installed.to_string()) {
+                notices.push(format!(
+                    "gitleaks {} is available (installed: {}). Run 'gitleaks-guard update'.",
+                    latest, installed
+                ));
+            }
+        }
+        if let Some(latest) = &self.latest_guard {
+            if is_newer(latest, installed_guard) {
+                notices.push(format!(
+                    "gitleaks-guard {} is available (installed: {}). Run 'gitleaks-guard self-update'.",
+                    latest, installed_guard
+                ));
+            }
+        }
+        notices
+    }
+}
+
+fn is_newer(latest: &str, installed: &str) -> bool {
+    matches!(
+        (gitleaks::parse_version(latest), gitleaks::parse_version(installed)),
+        (Some(latest), Some(installed)) if latest > installed
+    )
+}
+
+/// Print cached update notices after a command, and refresh the cache in the background when due
+///
+/// Skipped inside git hooks, when output is not a terminal, and when turned off in the settings
+/// or with `GITLEAKS_GUARD_NO_UPDATE_CHECK`.
+pub fn notify() {
+    if env::var_os(DISABLE_ENV).is_some()
+        || env::var_os("GIT_INDEX_FILE").is_some()
+        || !std::io::stdout().is_terminal()
+    {
+        return;
+    }
+
+    let interval = Settings::load()
+        .ok()
+        .and_then(|s| s.updates.check_interval_hours)
+        .unwrap_or(DEFAULT_INTERVAL_HOURS);
+    if interval == 0 {
+        return;
+    }
+
+    let state = UpdateState::load();
+    let installed = gitleaks::get_version().ok();
+    let notices = state.notices(installed.as_deref(), env!("CARGO_PKG_VERSION"));
+    if !notices.is_empty() {
+        println!();
+        for notice in notices {
+            println!("{} {}", "⬆".yellow().bold(), notice.yellow());
+        }
+    }
+
+    if state.is_stale(Utc::now().timestamp(), interval) {
+        spawn_refresh();
+    }
+}
+
+/// Refresh the cached state in a detached process so the current command exits immediately
+fn spawn_refresh() {
+    let Ok(exe) = env::current_exe() else {
+        return;
+    };
+    let mut command = Command::new(exe);
+    command
+        .arg("refresh-update-check")
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+
+    // Leave the terminal's process group so closing the terminal doesn't kill the check
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        command.process_group(0);
+    }
+    command.spawn().ok();
+}
+
+/// Look up the latest gitleaks and gitleaks-guard releases and cache the result
+pub fn refresh() -> Result<()> {
+    let mut state = UpdateState::load();
+    release::blocking(|| -> Result<()> {
+        let client = release::client()?;
+        let latest = |api_url: &str| {
+            release::fetch_release(&client, api_url, None)
+                .ok()
+                .map(|r| r.tag_name.trim_start_matches('v').to_string())
+        };
+        state.latest_gitleaks = latest(&release::gitleaks_api_url()?).or(state.latest_gitleaks.take());
+        state.latest_guard = latest(&release::self_api_url()).or(state.latest_guard.take());
+        Ok(())
+    })?;
+    state.checked_at = Utc::now().timestamp();
+    state.save()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_stale_state() {
+        let state = UpdateState {
+            checked_at: 1_000_000,
+            ..Default::default()
+        };
+        assert!(!state.is_stale(1_000_000 + 3599, 1));
+        assert!(state.is_stale(1_000_000 + 3600, 1));
+        assert!(UpdateState::default().is_stale(1_000_000, 24));
+    }
+
+    #[test]
+    fn reports_only_newer_releases() {
+        let state = UpdateState {
+            checked_at: 0,
+            latest_gitleaks: Some("8.19.0".to_string()),
+            latest_guard: Some("0.2.0".to_string()),
+        };
+        let notices = state.notices(Some("v8.18.0\n"), "0.2.0");
+        assert_eq!(notices.len(), 1);
+        assert!(notices[0].starts_with("gitleaks 8.19.0 is available"));
+
+        assert!(state.notices(Some("gitleaks version 8.18.0"), "0.2.0")[0].ends_with("(installed: 8.18.0). Run 'gitleaks-guard update'."));
+        assert!(state.notices(Some("8.19.0"), "0.1.0")[0].starts_with("gitleaks-guard 0.2.0"));
+        assert!(state.notices(None, "0.2.0").is_empty());
+    }
+}
@@ -1,6 +1,7 @@
 use anyhow::{Context, Result};
 use colored::*;
 use std::env;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 
 /// Detect the operating system
@@ -14,16 +15,32 @@ pub fn detect_os() -> Result<String> {
     }
 }
 
-/// Detect the system architecture
+/// Detect the system architecture, using gitleaks' asset naming
 pub fn detect_arch() -> Result<String> {
     let arch = env::consts::ARCH;
     match arch {
         "x86_64" => Ok("x64".to_string()),
+        "x86" => Ok("x32".to_string()),
         "aarch64" | "arm64" => Ok("arm64".to_string()),
+        "arm" => Ok(detect_arm_version().to_string()),
         _ => anyhow::bail!("Unsupported architecture: {}", arch),
     }
 }
 
+/// Distinguish 32-bit ARM hosts that can run armv7 binaries from armv6-only ones
+fn detect_arm_version() -> &'static str {
+    if cfg!(windows) {
+        return "armv7";
+    }
+    let machine = execute_command("uname", &["-m"]).unwrap_or_default();
+    let machine = machine.trim();
+    if machine.starts_with("armv7") || machine.starts_with("armv8") || machine == "aarch64" {
+        "armv7"
+    } else {
+        "armv6"
+    }
+}
+
 /// Execute a shell command and return output
 pub fn execute_command(cmd: &str, args: &[&str]) -> Result<String> {
     let output = Command::new(cmd)
@@ -79,3 +96,63 @@ pub fn command_exists(cmd: &str) -> bool {
             .unwrap_or(false)
     }
 }
+
+/// Check if the current process is running as root
+#[cfg(unix)]
+pub fn is_root() -> bool {
+    execute_command("id", &["-u"])
+        .map(|uid| uid.trim() == "0")
+        .unwrap_or(false)
+}
+
+/// Locate a command in PATH
+pub fn find_command(cmd: &str) -> Option<PathBuf> {
+    #[cfg(windows)]
+    let finder = "where";
+    #[cfg(not(windows))]
+    let finder = "which";
+
+    let output = execute_command(finder, &[cmd]).ok()?;
+    output.lines().next().map(|line| PathBuf::from(line.trim()))
+}
+
+/// Check whether the current user can create files in `dir`
+pub fn is_writable_dir(dir: &Path) -> bool {
+    tempfile::Builder::new()
+        .prefix(".gitleaks-guard-")
+        .tempfile_in(dir)
+        .is_ok()
+}
+
+/// Check if a directory is listed in PATH
+pub fn is_in_path(dir: &Path) -> bool {
+    env::var_os("PATH")
+        .map(|paths| env::split_paths(&paths).any(|p| p == dir))
+        .unwrap_or(false)
+}
+
+/// User-level configuration directory (`$XDG_CONFIG_HOME/gitleaks-guard`)
+pub fn config_dir() -> Option<PathBuf> {
+    base_dir("XDG_CONFIG_HOME", "APPDATA", ".config").map(|dir| dir.join("gitleaks-guard"))
+}
+
+/// User-level cache directory (`$XDG_CACHE_HOME/gitleaks-guard`)
+pub fn cache_dir() -> Option<PathBuf> {
+    base_dir("XDG_CACHE_HOME", "LOCALAPPDATA", ".cache").map(|dir| dir.join("gitleaks-guard"))
+}
+
+/// User-level data directory (`$XDG_DATA_HOME/gitleaks-guard`)
+pub fn data_dir() -> Option<PathBuf> {
+    base_dir("XDG_DATA_HOME", "LOCALAPPDATA", ".local/share").map(|dir| dir.join("gitleaks-guard"))
+}
+
+/// Resolve an XDG base directory, falling back to `~/<home_fallback>` or a Windows env var
+fn base_dir(xdg_var: &str, windows_var: &str, home_fallback: &str) -> Option<PathBuf> {
+    if let Some(dir) = env::var_os(xdg_var).filter(|d| !d.is_empty()) {
+        return Some(PathBuf::from(dir));
+    }
+    if cfg!(windows) {
+        return env::var_os(windows_var).map(PathBuf::from);
+    }
+    env::var_os("HOME").map(|home| PathBuf::from(home).join(home_fallback))
+}
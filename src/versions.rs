@@ -0,0 +1,324 @@
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::{gitleaks, utils};
+
+/// Side-by-side store of installed gitleaks versions
+///
+/// Each release lives at `<root>/<version>/gitleaks`; the binary in the install directory is a
+/// symlink to one of them (a copy on Windows), so switching versions never deletes anything.
+/// The active and previously active versions are recorded in `<root>/current` and
+/// `<root>/previous`, and the path of the installed binary in `<root>/installed`. Installs into the user's home keep the store in the user's data
+/// directory; system-wide install directories keep it next to them.
+pub struct VersionStore {
+    root: PathBuf,
+}
+
+impl VersionStore {
+    /// Open the store under the user's data directory
+    pub fn open() -> Result<Self> {
+        let dir = utils::data_dir().context("Could not determine the data directory")?;
+        Ok(Self::at(dir.join("versions")))
+    }
+
+    /// Open the store serving `install_dir`
+    ///
+    /// A system-wide install directory such as `/usr/local/bin` must not link into a home
+    /// directory other users cannot read, so its versions live in `/usr/local/lib/gitleaks-guard`.
+    pub fn for_install_dir(install_dir: &Path) -> Result<Self> {
+        let install_dir = std::path::absolute(install_dir)
+            .with_context(|| format!("Failed to resolve {}", install_dir.display()))?;
+        let home = std::env::var_os("HOME").map(PathBuf::from);
+        match system_root(&install_dir, home.as_deref()) {
+            Some(root) => Ok(Self::at(root)),
+            None => Self::open(),
+        }
+    }
+
+    /// Open a store rooted at `root`
+    pub fn at(root: PathBuf) -> Self {
+        VersionStore { root }
+    }
+
+    /// Directory holding the stored versions and the `current`/`previous` markers
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Check whether `binary` was put in place by this store
+    ///
+    /// On Unix that means a link into the store. Windows installs copies, so there the path
+    /// recorded by the last [`activate`](Self::activate) has to match.
+    pub fn owns(&self, binary: &Path) -> bool {
+        if cfg!(windows) {
+            let recorded = self.read_marker("installed");
+            return recorded.is_some_and(|recorded| {
+                std::path::absolute(binary).is_ok_and(|binary| binary == Path::new(&recorded))
+            });
+        }
+        let is_link = binary.symlink_metadata().is_ok_and(|m| m.file_type().is_symlink());
+        match (binary.canonicalize(), self.root.canonicalize()) {
+            (Ok(target), Ok(root)) => is_link && target.starts_with(root),
+            _ => false,
+        }
+    }
+
+    /// Path of the stored binary for `version`, if it is installed
+    pub fn binary(&self, version: &str) -> Option<PathBuf> {
+        let path = self.root.join(bare(version)).join(gitleaks::BINARY_NAME);
+        path.is_file().then_some(path)
+    }
+
+    /// Copy a verified binary into the store under `version`
+    pub fn add(&self, version: &str, binary: &Path) -> Result<PathBuf> {
+        let dir = self.root.join(bare(version));
+        fs::create_dir_all(&dir)
+            .with_context(|| format!("Failed to create {}", dir.display()))?;
+
+        let path = dir.join(gitleaks::BINARY_NAME);
+        let staging = dir.join(format!(".{}.new", gitleaks::BINARY_NAME));
+        fs::copy(binary, &staging)
+            .with_context(|| format!("Failed to write {}", staging.display()))?;
+        make_executable(&staging)?;
+        fs::rename(&staging, &path)
+            .with_context(|| format!("Failed to store gitleaks {}", bare(version)))?;
+        Ok(path)
+    }
+
+    /// Installed versions, oldest first
+    pub fn versions(&self) -> Result<Vec<String>> {
+        let mut versions = Vec::new();
+        if !self.root.exists() {
+            return Ok(versions);
+        }
+        for entry in fs::read_dir(&self.root)
+            .with_context(|| format!("Failed to read {}", self.root.display()))?
+            .filter_map(|e| e.ok())
+        {
+            let name = entry.file_name().to_string_lossy().to_string();
+            if entry.path().join(gitleaks::BINARY_NAME).is_file() {
+                versions.push(name);
+            }
+        }
+        versions.sort_by(|a, b| match (semver::Version::parse(a), semver::Version::parse(b)) {
+            (Ok(a), Ok(b)) => a.cmp(&b),
+            _ => a.cmp(b),
+        });
+        Ok(versions)
+    }
+
+    /// The version the install directory currently points at
+    pub fn current(&self) -> Option<String> {
+        self.read_marker("current")
+    }
+
+    /// The version that was active before the last switch
+    pub fn previous(&self) -> Option<String> {
+        self.read_marker("previous")
+    }
+
+    /// Point `install_dir/gitleaks` at a stored version, returning the path of the link
+    pub fn activate(&self, version: &str, install_dir: &Path) -> Result<PathBuf> {
+        let version = bare(version);
+        let target = self.binary(version).with_context(|| {
+            format!("gitleaks {} is not installed. Install it with 'gitleaks-guard update --version {}'", version, version)
+        })?;
+
+        fs::create_dir_all(install_dir).with_context(|| {
+            format!(
+                "Failed to create {}. Choose a writable location with --install-dir",
+                install_dir.display()
+            )
+        })?;
+
+        let link = install_dir.join(gitleaks::BINARY_NAME);
+        let previous = self.current().or_else(|| self.adopt(&link));
+
+        // Build the new link next to the old one and rename it over, so gitleaks never disappears
+        let staging = install_dir.join(format!(".{}.new", gitleaks::BINARY_NAME));
+        if staging.symlink_metadata().is_ok() {
+            fs::remove_file(&staging).ok();
+        }
+        link_or_copy(&target, &staging).with_context(|| {
+            format!(
+                "Failed to write {}. Choose a writable location with --install-dir",
+                staging.display()
+            )
+        })?;
+        fs::rename(&staging, &link)
+            .with_context(|| format!("Failed to install gitleaks to {}", link.display()))?;
+
+        if let Some(previous) = previous.filter(|p| p != version) {
+            self.write_marker("previous", &previous)?;
+        }
+        self.write_marker("current", version)?;
+        if let Ok(installed) = std::path::absolute(&link) {
+            self.write_marker("installed", &installed.to_string_lossy())?;
+        }
+        Ok(link)
+    }
+
+    /// Switch back to the previously active version
+    pub fn rollback(&self, install_dir: &Path) -> Result<(String, PathBuf)> {
+        let previous = self
+            .previous()
+            .context("No previous gitleaks version to roll back to")?;
+        let link = self.activate(&previous, install_dir)?;
+        Ok((previous, link))
+    }
+
+    /// Keep a binary installed before versions were tracked, so it can still be rolled back to
+    fn adopt(&self, existing: &Path) -> Option<String> {
+        let metadata = existing.symlink_metadata().ok()?;
+        if !metadata.is_file() {
+            return None;
+        }
+        let output = gitleaks::get_version_at(existing).ok()?;
+        let version = gitleaks::parse_version(&output)?.to_string();
+        if self.binary(&version).is_none() {
+            self.add(&version, existing).ok()?;
+        }
+        Some(version)
+    }
+
+    fn read_marker(&self, name: &str) -> Option<String> {
+        fs::read_to_string(self.root.join(name))
+            .ok()
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty())
+    }
+
+    fn write_marker(&self, name: &str, version: &str) -> Result<()> {
+        fs::create_dir_all(&self.root)
+            .with_context(|| format!("Failed to create {}", self.root.display()))?;
+        fs::write(self.root.join(name), format!("{}\n", version))
+            .with_context(|| format!("Failed to record {} gitleaks version", name))
+    }
+}
+
+/// Store root for an install directory outside `home`, or `None` to use the user store
+///
+/// `<prefix>/bin` gets `<prefix>/lib/gitleaks-guard/versions`; any other directory keeps the
+/// store in a hidden `.gitleaks-guard` directory of its own. Windows copies binaries instead of
+/// linking them, so it always uses the user store.
+fn system_root(install_dir: &Path, home: Option<&Path>) -> Option<PathBuf> {
+    if cfg!(windows) || home.is_some_and(|home| install_dir.starts_with(home)) {
+        return None;
+    }
+    match (install_dir.file_name(), install_dir.parent()) {
+        (Some(name), Some(prefix)) if name == "bin" => {
+            Some(prefix.join("lib").join("gitleaks-guard").join("versions"))
+        }
+        _ => Some(install_dir.join(".gitleaks-guard").join("versions")),
+    }
+}
+
+fn bare(version: &str) -> &str {
+    version.trim_start_matches('v')
+}
+
+#[cfg(unix)]
+fn link_or_copy(target: &Path, link: &Path) -> std::io::Result<()> {
+    std::os::unix::fs::symlink(target, link)
+}
+
+#[cfg(not(unix))]
+fn link_or_copy(target: &Path, link: &Path) -> std::io::Result<()> {
+    fs::copy(target, link).map(|_| ())
+}
+
+fn make_executable(_path: &Path) -> Result<()> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(_path, fs::Permissions::from_mode(0o755))
+            .context("Failed to make gitleaks executable")?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fake_binary(dir: &Path, version: &str) -> PathBuf {
+        let path = dir.join(format!("gitleaks-{}", version));
+        fs::write(&path, format!("#!/bin/sh\necho {}\n", version)).unwrap();
+        path
+    }
+
+    #[test]
+    fn switches_and_rolls_back() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = VersionStore::at(dir.path().join("versions"));
+        let bin = dir.path().join("bin");
+
+        store.add("v8.18.0", &fake_binary(dir.path(), "8.18.0")).unwrap();
+        store.add("8.19.0", &fake_binary(dir.path(), "8.19.0")).unwrap();
+        assert_eq!(store.versions().unwrap(), vec!["8.18.0", "8.19.0"]);
+
+        store.activate("8.18.0", &bin).unwrap();
+        let link = store.activate("v8.19.0", &bin).unwrap();
+        #[cfg(unix)]
+        assert!(store.owns(&link));
+        assert!(!store.owns(&dir.path().join("gitleaks-8.19.0")));
+        assert_eq!(store.current().as_deref(), Some("8.19.0"));
+        assert!(fs::read_to_string(&link).unwrap().contains("8.19.0"));
+
+        let (version, link) = store.rollback(&bin).unwrap();
+        assert_eq!(version, "8.18.0");
+        assert!(fs::read_to_string(&link).unwrap().contains("8.18.0"));
+        assert_eq!(store.previous().as_deref(), Some("8.19.0"));
+    }
+
+    #[test]
+    fn refuses_unknown_version() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = VersionStore::at(dir.path().join("versions"));
+        assert!(store.activate("8.18.0", &dir.path().join("bin")).is_err());
+        assert!(store.rollback(&dir.path().join("bin")).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn system_install_dir_links_outside_home() {
+        let home = tempfile::tempdir().unwrap();
+        let prefix = tempfile::tempdir().unwrap();
+        let bin = prefix.path().join("bin");
+
+        let root = system_root(&bin, Some(home.path())).unwrap();
+        assert_eq!(root, prefix.path().join("lib/gitleaks-guard/versions"));
+        assert_eq!(
+            system_root(Path::new("/opt/tools"), Some(home.path())),
+            Some(PathBuf::from("/opt/tools/.gitleaks-guard/versions"))
+        );
+        assert_eq!(system_root(&home.path().join(".local/bin"), Some(home.path())), None);
+
+        let store = VersionStore::at(root);
+        store.add("8.18.0", &fake_binary(prefix.path(), "8.18.0")).unwrap();
+        let link = store.activate("8.18.0", &bin).unwrap();
+        let target = fs::read_link(&link).unwrap();
+        assert!(target.starts_with(prefix.path().join("lib")));
+        assert!(!target.starts_with(home.path()));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn adopts_untracked_binary_by_parsed_version() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let store = VersionStore::at(dir.path().join("versions"));
+        let bin = dir.path().join("bin");
+        fs::create_dir_all(&bin).unwrap();
+        let existing = bin.join(gitleaks::BINARY_NAME);
+        fs::write(&existing, "#!/bin/sh\necho gitleaks version 8.17.0\n").unwrap();
+        fs::set_permissions(&existing, fs::Permissions::from_mode(0o755)).unwrap();
+
+        store.add("8.18.0", &fake_binary(dir.path(), "8.18.0")).unwrap();
+        store.activate("8.18.0", &bin).unwrap();
+        assert_eq!(store.previous().as_deref(), Some("8.17.0"));
+        assert_eq!(store.versions().unwrap(), vec!["8.17.0", "8.18.0"]);
+    }
+}
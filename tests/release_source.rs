@@ -0,0 +1,148 @@
+//! Drives the binary against a local HTTP stand-in for the GitHub releases API.
+#![cfg(all(target_os = "linux", target_arch = "x86_64"))]
+
+use std::fs;
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::thread;
+
+const ARCHIVE: &str = "gitleaks_8.18.0_linux_x64.tar.gz";
+const CHECKSUMS: &str = "gitleaks_8.18.0_checksums.txt";
+
+fn fixture(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name)
+}
+
+/// Serve fake release JSON and the fixture assets, returning the API base URL
+fn serve_releases() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let base = format!("http://{}", listener.local_addr().unwrap());
+    let release = format!(
+        r#"{{"tag_name":"v8.18.0","assets":[
+            {{"name":"{archive}","browser_download_url":"{base}/download/{archive}"}},
+            {{"name":"{checksums}","browser_download_url":"{base}/download/{checksums}"}}
+        ]}}"#,
+        archive = ARCHIVE,
+        checksums = CHECKSUMS,
+        base = base
+    );
+    let releases = format!(
+        r#"[{release},{{"tag_name":"v8.17.0","assets":[],"body":"* Old news"}}]"#,
+        release = release.replacen(
+            r#""tag_name":"v8.18.0","#,
+            r#""tag_name":"v8.18.0","body":"* 1a2b3c4d add Stripe rule\n* bump deps","#,
+            1
+        )
+    );
+
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = stream.unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut request_line = String::new();
+            reader.read_line(&mut request_line).unwrap();
+            loop {
+                let mut header = String::new();
+                if reader.read_line(&mut header).unwrap() == 0 || header == "\r\n" {
+                    break;
+                }
+            }
+
+            let path = request_line.split_whitespace().nth(1).unwrap_or("");
+            let (status, body) = match path {
+                "/repos/gitleaks/gitleaks/releases/latest" => ("200 OK", release.clone().into_bytes()),
+                p if p.starts_with("/repos/gitleaks/gitleaks/releases?") => ("200 OK", releases.clone().into_bytes()),
+                p if p.starts_with("/download/") => {
+                    match fs::read(fixture(p.trim_start_matches("/download/"))) {
+                        Ok(bytes) => ("200 OK", bytes),
+                        Err(_) => ("404 Not Found", Vec::new()),
+                    }
+                }
+                _ => ("404 Not Found", Vec::new()),
+            };
+
+            let head = format!(
+                "HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                status,
+                body.len()
+            );
+            stream.write_all(head.as_bytes()).unwrap();
+            stream.write_all(&body).unwrap();
+        }
+    });
+
+    format!("{}/repos/gitleaks/gitleaks", base)
+}
+
+/// Write a stand-in gitleaks that reports an older version
+fn write_fake_gitleaks(path: &Path) {
+    use std::os::unix::fs::PermissionsExt;
+    fs::write(path, "#!/bin/sh\necho 8.17.0\n").unwrap();
+    fs::set_permissions(path, fs::Permissions::from_mode(0o755)).unwrap();
+}
+
+#[test]
+fn update_installs_from_configured_release_source() {
+    let api_url = serve_releases();
+    let work = tempfile::tempdir().unwrap();
+
+    // An older gitleaks on PATH so `update` has something to replace
+    let fake_bin = work.path().join("bin");
+    fs::create_dir_all(&fake_bin).unwrap();
+    let fake = fake_bin.join("gitleaks");
+    write_fake_gitleaks(&fake);
+
+    let path = format!("{}:{}", fake_bin.display(), std::env::var("PATH").unwrap());
+    let run_update = |extra: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_gitleaks-guard"))
+            .args(["update", "--force"])
+            .args(extra)
+            .current_dir(work.path())
+            .env("PATH", &path)
+            .env("GITLEAKS_GUARD_RELEASES_URL", &api_url)
+            .env("GITLEAKS_GUARD_CONFIG", work.path().join("missing.toml"))
+            .env("XDG_CACHE_HOME", work.path().join("cache"))
+            .env("XDG_DATA_HOME", work.path().join("data"))
+            .output()
+            .unwrap()
+    };
+    let update = || {
+        let output = run_update(&[]);
+        let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+        assert!(
+            output.status.success(),
+            "update failed:\n{}\n{}",
+            stdout,
+            String::from_utf8_lossy(&output.stderr)
+        );
+        stdout
+    };
+
+    let first = update();
+    assert!(first.contains("Latest version: 8.18.0"));
+    assert!(first.contains("Downloading gitleaks v8.18.0"));
+    assert!(first.contains("add Stripe rule"));
+    assert!(!first.contains("Old news"));
+    // The gitleaks found on PATH is replaced in place, linking into a store next to it
+    assert!(fake.is_file());
+    assert_eq!(
+        fs::read_link(&fake).unwrap(),
+        work.path().join("lib/gitleaks-guard/versions/8.18.0/gitleaks")
+    );
+
+    // The verified archive is cached, so the second run skips the download
+    let second = update();
+    assert!(second.contains("Using cached gitleaks v8.18.0"));
+
+    // Installing somewhere later in PATH would leave hooks on the old binary
+    fs::remove_file(&fake).unwrap();
+    write_fake_gitleaks(&fake);
+    let shadowed = run_update(&["--install-dir", work.path().join("other/bin").to_str().unwrap()]);
+    assert!(!shadowed.status.success());
+    assert!(String::from_utf8_lossy(&shadowed.stderr).contains("comes first in your PATH"));
+    assert!(!work.path().join("other/bin/gitleaks").exists());
+}